@@ -0,0 +1,715 @@
+//! # Apple App Site Association.
+//!
+//! The file a website hosts to associate its domain with apps.
+//!
+//! Apps declare the domains they are associated with in the associated domains
+//! entitlement, and every one of those domains hosts an `apple-app-site-association`
+//! JSON file at `https://<fully qualified domain>/.well-known/apple-app-site-association`
+//! listing the apps allowed to use it for each service. Both sides must agree for
+//! universal links, shared web credentials, Handoff, and App Clips to work.
+//!
+//! Official documentation: <https://developer.apple.com/documentation/bundleresources/applinks>
+//!
+//! ## Framework
+//! * Bundle Resources
+
+use crate::{
+    entitlements::prelude::*,
+    error::{Error, Result},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The contents of an `apple-app-site-association` file.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct AppSiteAssociation {
+    /// The apps that handle universal links for the domain.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub applinks: Option<AppLinks>,
+    /// The apps that use the domain for shared web credentials.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub webcredentials: Option<AppList>,
+    /// The App Clips associated with the domain.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub appclips: Option<AppList>,
+    /// The apps that use the domain for Handoff.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub activitycontinuation: Option<AppList>,
+}
+
+/// The universal links section of an `apple-app-site-association` file.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct AppLinks {
+    /// Legacy list of apps. Must be present and empty for iOS 12 and earlier.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub apps: Option<Vec<String>>,
+    /// The apps and the URLs each of them handles.
+    #[serde(default)]
+    pub details: Vec<AppLinksDetails>,
+    /// Variables that components can reference as `$(name)`.
+    #[serde(
+        rename = "substitutionVariables",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub substitution_variables: Option<BTreeMap<String, Vec<String>>>,
+    /// The default matching options for all components in the file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub defaults: Option<ComponentDefaults>,
+}
+
+/// The URLs handled by a group of apps.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct AppLinksDetails {
+    /// The application identifiers of the apps, in the `<team id>.<bundle id>` format.
+    #[serde(rename = "appIDs", skip_serializing_if = "Option::is_none")]
+    pub app_ids: Option<Vec<String>>,
+    /// Legacy single application identifier used before iOS 13.
+    #[serde(rename = "appID", skip_serializing_if = "Option::is_none")]
+    pub app_id: Option<String>,
+    /// Legacy path patterns used before iOS 13. Patterns starting with `NOT ` exclude
+    /// the matching paths.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paths: Option<Vec<String>>,
+    /// The URL components the apps handle, evaluated in order.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub components: Option<Vec<Component>>,
+    /// The default matching options for the components of these apps.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub defaults: Option<ComponentDefaults>,
+}
+
+/// A pattern that URLs are matched against.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct Component {
+    /// Pattern for the URL path. `*` matches any run of characters and `?` matches
+    /// a single character.
+    #[serde(rename = "/", skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Pattern for the URL query.
+    #[serde(rename = "?", skip_serializing_if = "Option::is_none")]
+    pub query: Option<QueryComponent>,
+    /// Pattern for the URL fragment.
+    #[serde(rename = "#", skip_serializing_if = "Option::is_none")]
+    pub fragment: Option<String>,
+    /// Whether URLs matching the component are excluded instead of handled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exclude: Option<bool>,
+    /// A comment ignored by the system.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// Whether patterns are matched case-sensitively. Defaults to `true`.
+    #[serde(rename = "caseSensitive", skip_serializing_if = "Option::is_none")]
+    pub case_sensitive: Option<bool>,
+    /// Whether patterns are matched against the percent-encoded URL. Defaults to
+    /// `true`.
+    #[serde(rename = "percentEncoded", skip_serializing_if = "Option::is_none")]
+    pub percent_encoded: Option<bool>,
+}
+
+impl Component {
+    /// Creates a component matching the given path pattern.
+    pub fn path(pattern: impl Into<String>) -> Self {
+        Self {
+            path: Some(pattern.into()),
+            ..Default::default()
+        }
+    }
+}
+
+/// Pattern for the query of a URL.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum QueryComponent {
+    /// Pattern matched against the whole query string.
+    Pattern(String),
+    /// Patterns matched against the values of individual query items.
+    Items(BTreeMap<String, String>),
+}
+
+/// Default matching options for components.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct ComponentDefaults {
+    /// Whether patterns are matched case-sensitively.
+    #[serde(rename = "caseSensitive", skip_serializing_if = "Option::is_none")]
+    pub case_sensitive: Option<bool>,
+    /// Whether patterns are matched against the percent-encoded URL.
+    #[serde(rename = "percentEncoded", skip_serializing_if = "Option::is_none")]
+    pub percent_encoded: Option<bool>,
+}
+
+/// The apps associated with a service.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct AppList {
+    /// The application identifiers, in the `<team id>.<bundle id>` format.
+    pub apps: Vec<String>,
+}
+
+impl AppSiteAssociation {
+    /// Generates the `apple-app-site-association` file of every domain listed in the
+    /// associated domains entitlement, keyed by the host serving it.
+    ///
+    /// A wildcard domain such as `*.example.com` is served by `example.com`. Invalid
+    /// entries of the entitlement are skipped.
+    ///
+    /// The app is identified as `<team_id>.<bundle_id>`. Domains associated with
+    /// `applinks` handle the given `components`. The alternate mode of an entry doesn't
+    /// affect the generated file. Use [`AppSiteAssociation::merge`] to combine the files
+    /// generated for several apps sharing a domain.
+    pub fn from_entitlements(
+        entitlements: &Entitlements,
+        team_id: &str,
+        bundle_id: &str,
+        components: &[Component],
+    ) -> BTreeMap<String, AppSiteAssociation> {
+        let app_id = format!("{}.{}", team_id, bundle_id);
+        let mut files = BTreeMap::<String, AppSiteAssociation>::new();
+        let domains = entitlements.networking.associated_domains.iter().flatten();
+        for domain in domains.filter_map(AssociatedDomainEntry::domain) {
+            let file = files.entry(domain.file_host().to_owned()).or_default();
+            let list = match domain.service {
+                AssociatedDomainService::AppLinks => {
+                    let applinks = file.applinks.get_or_insert_with(Default::default);
+                    let details = AppLinksDetails {
+                        app_ids: Some(vec![app_id.clone()]),
+                        components: Some(components.to_vec()),
+                        ..Default::default()
+                    };
+                    if !applinks.details.contains(&details) {
+                        applinks.details.push(details);
+                    }
+                    continue;
+                }
+                AssociatedDomainService::WebCredentials => &mut file.webcredentials,
+                AssociatedDomainService::AppClips => &mut file.appclips,
+                AssociatedDomainService::ActivityContinuation => &mut file.activitycontinuation,
+            };
+            list.get_or_insert_with(Default::default).push(&app_id);
+        }
+        files
+    }
+
+    /// Merges the apps of another file for the same domain into this one.
+    pub fn merge(&mut self, other: AppSiteAssociation) {
+        if let Some(other) = other.applinks {
+            let applinks = self.applinks.get_or_insert_with(Default::default);
+            for details in other.details {
+                if !applinks.details.contains(&details) {
+                    applinks.details.push(details);
+                }
+            }
+            if let Some(variables) = other.substitution_variables {
+                applinks
+                    .substitution_variables
+                    .get_or_insert_with(Default::default)
+                    .extend(variables);
+            }
+            if applinks.defaults.is_none() {
+                applinks.defaults = other.defaults;
+            }
+        }
+        for (list, other) in [
+            (&mut self.webcredentials, other.webcredentials),
+            (&mut self.appclips, other.appclips),
+            (&mut self.activitycontinuation, other.activitycontinuation),
+        ] {
+            for app in other.into_iter().flat_map(|other| other.apps) {
+                list.get_or_insert_with(Default::default).push(&app);
+            }
+        }
+    }
+
+    /// Parses an `apple-app-site-association` file.
+    #[cfg(feature = "serde_json")]
+    pub fn from_json(json: &[u8]) -> Result<Self> {
+        Ok(serde_json::from_slice(json)?)
+    }
+
+    /// Serializes the file as pretty-printed JSON, ready to be served.
+    #[cfg(feature = "serde_json")]
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// Returns `true` if the app opens `url` as a universal link.
+///
+/// The URL host is looked up in the `applinks` entries of the associated domains
+/// entitlement, preferring the most specific entry when several wildcards match. The
+/// `apple-app-site-association` file of that entry is taken from `files`, keyed by the
+/// host serving it as in [`AppSiteAssociation::from_entitlements`], and must
+/// declare that `app_id` handles the URL.
+pub fn opens_universal_link(
+    entitlements: &Entitlements,
+    app_id: &str,
+    url: &str,
+    files: &BTreeMap<String, AppSiteAssociation>,
+) -> Result<bool> {
+    let parsed = Url::parse(url)?;
+    let domain = entitlements
+        .networking
+        .associated_domains
+        .iter()
+        .flatten()
+        .filter_map(AssociatedDomainEntry::domain)
+        .filter(|domain| domain.service == AssociatedDomainService::AppLinks)
+        .filter(|domain| domain.matches_host(parsed.host))
+        .max_by_key(|domain| (!domain.is_wildcard(), domain.domain.len()));
+    let file = domain.and_then(|domain| files.get(domain.file_host()));
+    Ok(file.is_some_and(|file| file.handles(app_id, &parsed)))
+}
+
+impl AppSiteAssociation {
+    /// Returns `true` if this file declares that `app_id` handles `url`.
+    ///
+    /// Only the path, query and fragment of the URL are matched; the host is expected to
+    /// be the one serving the file. Components take precedence over legacy `paths` when
+    /// both are present, and the first component or path that matches decides whether
+    /// the URL is handled or excluded.
+    pub fn handles_url(&self, app_id: &str, url: &str) -> Result<bool> {
+        Ok(self.handles(app_id, &Url::parse(url)?))
+    }
+
+    fn handles(&self, app_id: &str, url: &Url) -> bool {
+        let applinks = match &self.applinks {
+            Some(applinks) => applinks,
+            None => return false,
+        };
+        let variables = applinks.substitution_variables.as_ref();
+        for details in &applinks.details {
+            let listed = details.app_ids.iter().flatten().any(|id| id == app_id)
+                || details.app_id.as_deref() == Some(app_id);
+            if !listed {
+                continue;
+            }
+            let defaults = [details.defaults.as_ref(), applinks.defaults.as_ref()];
+            if let Some(components) = &details.components {
+                for component in components {
+                    if component.matches(url, defaults, variables) {
+                        return !component.exclude.unwrap_or(false);
+                    }
+                }
+            } else if let Some(paths) = &details.paths {
+                for path in paths {
+                    let (exclude, pattern) = match path.strip_prefix("NOT ") {
+                        Some(pattern) => (true, pattern),
+                        None => (false, path.as_str()),
+                    };
+                    if Pattern::new(pattern, None, true).matches(url.path) {
+                        return !exclude;
+                    }
+                }
+            }
+        }
+        false
+    }
+}
+
+impl Component {
+    fn matches(
+        &self,
+        url: &Url,
+        defaults: [Option<&ComponentDefaults>; 2],
+        variables: Option<&BTreeMap<String, Vec<String>>>,
+    ) -> bool {
+        let case_sensitive = self
+            .case_sensitive
+            .or_else(|| defaults.iter().flatten().find_map(|d| d.case_sensitive))
+            .unwrap_or(true);
+        let percent_encoded = self
+            .percent_encoded
+            .or_else(|| defaults.iter().flatten().find_map(|d| d.percent_encoded))
+            .unwrap_or(true);
+        let decode = |value: &str| match percent_encoded {
+            true => value.to_owned(),
+            false => percent_decode(value),
+        };
+        let matches = |pattern: &str, value: &str| {
+            Pattern::new(pattern, variables, case_sensitive).matches(&decode(value))
+        };
+        let query = url.query.unwrap_or_default();
+        let query_matches = match &self.query {
+            Some(QueryComponent::Pattern(pattern)) => matches(pattern, query),
+            Some(QueryComponent::Items(items)) => items.iter().all(|(name, pattern)| {
+                query
+                    .split('&')
+                    .map(|item| item.split_once('=').unwrap_or((item, "")))
+                    .filter(|(key, _)| decode(key) == decode(name))
+                    .any(|(_, value)| matches(pattern, value))
+            }),
+            None => true,
+        };
+        self.path
+            .as_ref()
+            .is_none_or(|path| matches(path, url.path))
+            && query_matches
+            && self
+                .fragment
+                .as_ref()
+                .is_none_or(|fragment| matches(fragment, url.fragment.unwrap_or_default()))
+    }
+}
+
+/// The parts of an `https` URL that universal links match against.
+struct Url<'a> {
+    host: &'a str,
+    path: &'a str,
+    query: Option<&'a str>,
+    fragment: Option<&'a str>,
+}
+
+impl<'a> Url<'a> {
+    fn parse(url: &'a str) -> Result<Self> {
+        let invalid = || Error::InvalidUrl(url.to_owned());
+        let (scheme, rest) = url.split_once("://").ok_or_else(invalid)?;
+        if !scheme.eq_ignore_ascii_case("https") {
+            return Err(invalid());
+        }
+        let (rest, fragment) = match rest.split_once('#') {
+            Some((rest, fragment)) => (rest, Some(fragment)),
+            None => (rest, None),
+        };
+        let (rest, query) = match rest.split_once('?') {
+            Some((rest, query)) => (rest, Some(query)),
+            None => (rest, None),
+        };
+        let (authority, path) = match rest.find('/') {
+            Some(index) => rest.split_at(index),
+            None => (rest, "/"),
+        };
+        let host = authority.rsplit('@').next().unwrap_or_default();
+        let host = host.split(':').next().unwrap_or_default();
+        if host.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Self {
+            host,
+            path,
+            query,
+            fragment,
+        })
+    }
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let hex = bytes
+            .get(index + 1..index + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[index], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// A component pattern where `*` matches any run of characters, `?` matches a single
+/// character and `$(name)` matches any value of a substitution variable.
+struct Pattern {
+    tokens: Vec<Token>,
+    case_sensitive: bool,
+}
+
+enum Token {
+    Char(char),
+    AnyChar,
+    AnyRun,
+    OneOf(Vec<Vec<char>>),
+}
+
+impl Pattern {
+    fn new(
+        pattern: &str,
+        variables: Option<&BTreeMap<String, Vec<String>>>,
+        case_sensitive: bool,
+    ) -> Self {
+        let fold = |c: char| match case_sensitive {
+            true => c,
+            false => c.to_lowercase().next().unwrap_or(c),
+        };
+        let mut tokens = Vec::new();
+        let mut rest = pattern;
+        while let Some(c) = rest.chars().next() {
+            let variable = rest
+                .strip_prefix("$(")
+                .and_then(|tail| tail.split_once(')'))
+                .and_then(|(name, tail)| Some((variables?.get(name)?, tail)));
+            if let Some((values, tail)) = variable {
+                let values = values.iter().map(|v| v.chars().map(fold).collect());
+                tokens.push(Token::OneOf(values.collect()));
+                rest = tail;
+                continue;
+            }
+            tokens.push(match c {
+                '*' => Token::AnyRun,
+                '?' => Token::AnyChar,
+                c => Token::Char(fold(c)),
+            });
+            rest = &rest[c.len_utf8()..];
+        }
+        Self {
+            tokens,
+            case_sensitive,
+        }
+    }
+
+    fn matches(&self, value: &str) -> bool {
+        let value: Vec<char> = match self.case_sensitive {
+            true => value.chars().collect(),
+            false => value
+                .chars()
+                .map(|c| c.to_lowercase().next().unwrap_or(c))
+                .collect(),
+        };
+        self.matches_chars(&value)
+    }
+
+    /// Matches over every `(token, offset)` pair once, from the end of the pattern, so
+    /// that runs of `*` don't backtrack exponentially.
+    fn matches_chars(&self, value: &[char]) -> bool {
+        let width = value.len() + 1;
+        // `matched[i * width + j]` is whether `tokens[i..]` matches `value[j..]`.
+        let mut matched = vec![false; (self.tokens.len() + 1) * width];
+        matched[self.tokens.len() * width + value.len()] = true;
+        for (i, token) in self.tokens.iter().enumerate().rev() {
+            for j in (0..width).rev() {
+                let next = |skip: usize| j + skip < width && matched[(i + 1) * width + j + skip];
+                let is_match = match token {
+                    Token::Char(c) => value.get(j) == Some(c) && next(1),
+                    Token::AnyChar => next(1),
+                    Token::AnyRun => next(0) || (j < value.len() && matched[i * width + j + 1]),
+                    Token::OneOf(values) => values.iter().any(|candidate| {
+                        value[j..].starts_with(candidate) && next(candidate.len())
+                    }),
+                };
+                matched[i * width + j] = is_match;
+            }
+        }
+        matched[0]
+    }
+}
+
+impl AppList {
+    fn push(&mut self, app_id: &str) {
+        if !self.apps.iter().any(|app| app == app_id) {
+            self.apps.push(app_id.to_owned());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generates_file_per_associated_domain() {
+        let entitlements = Entitlements {
+            networking: Networking {
+                associated_domains: Some(vec![
+                    "applinks:example.com".parse().unwrap(),
+                    "webcredentials:example.com".parse().unwrap(),
+                    "applinks:*.example.org?mode=developer".parse().unwrap(),
+                ]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let components = [Component::path("/buy/*")];
+        let files = AppSiteAssociation::from_entitlements(
+            &entitlements,
+            "ABCDE12345",
+            "com.example.app",
+            &components,
+        );
+        assert_eq!(
+            files.keys().collect::<Vec<_>>(),
+            ["example.com", "example.org"]
+        );
+        let file = &files["example.com"];
+        assert_eq!(
+            file.webcredentials.as_ref().unwrap().apps,
+            ["ABCDE12345.com.example.app"]
+        );
+        let details = &file.applinks.as_ref().unwrap().details;
+        assert_eq!(details.len(), 1);
+        assert_eq!(details[0].components.as_deref(), Some(&components[..]));
+        assert!(files["example.org"].webcredentials.is_none());
+    }
+
+    #[cfg(feature = "plist")]
+    #[test]
+    fn keeps_invalid_associated_domains() {
+        let plist = br#"<?xml version="1.0" encoding="UTF-8"?>
+<plist version="1.0">
+<dict>
+	<key>com.apple.developer.associated-domains</key>
+	<array>
+		<string>applinks:example.com</string>
+		<string>example.org</string>
+	</array>
+</dict>
+</plist>"#;
+        let entitlements: Entitlements = plist::from_bytes(plist).unwrap();
+        let domains = entitlements.networking.associated_domains.as_ref().unwrap();
+        assert_eq!(
+            domains,
+            &[
+                "applinks:example.com".parse().unwrap(),
+                AssociatedDomainEntry::Invalid("example.org".to_owned()),
+            ]
+        );
+        let files = AppSiteAssociation::from_entitlements(&entitlements, "ABCDE12345", "app", &[]);
+        assert_eq!(files.keys().collect::<Vec<_>>(), ["example.com"]);
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn json_roundtrip() {
+        let json = r##"{
+  "applinks": {
+    "details": [
+      {
+        "appIDs": [
+          "ABCDE12345.com.example.app"
+        ],
+        "components": [
+          {
+            "#": "no_universal_links",
+            "exclude": true
+          },
+          {
+            "/": "/buy/*",
+            "?": {
+              "ref": "?*"
+            },
+            "caseSensitive": false
+          }
+        ]
+      }
+    ]
+  },
+  "appclips": {
+    "apps": [
+      "ABCDE12345.com.example.app.Clip"
+    ]
+  }
+}"##;
+        let file = AppSiteAssociation::from_json(json.as_bytes()).unwrap();
+        let details = &file.applinks.as_ref().unwrap().details[0];
+        assert_eq!(
+            details.components.as_ref().unwrap()[1].query,
+            Some(QueryComponent::Items(
+                [("ref".to_owned(), "?*".to_owned())].into()
+            ))
+        );
+        assert_eq!(file.to_json().unwrap(), json);
+    }
+
+    #[test]
+    fn matches_components_in_order() {
+        let file = AppSiteAssociation {
+            applinks: Some(AppLinks {
+                details: vec![AppLinksDetails {
+                    app_ids: Some(vec!["ABCDE12345.com.example.app".to_owned()]),
+                    components: Some(vec![
+                        Component {
+                            fragment: Some("no_universal_links".to_owned()),
+                            exclude: Some(true),
+                            ..Default::default()
+                        },
+                        Component {
+                            path: Some("/buy/$(region)/*".to_owned()),
+                            query: Some(QueryComponent::Items(
+                                [("ref".to_owned(), "?*".to_owned())].into(),
+                            )),
+                            ..Default::default()
+                        },
+                        Component {
+                            path: Some("/help/CAFÉ".to_owned()),
+                            case_sensitive: Some(false),
+                            percent_encoded: Some(false),
+                            ..Default::default()
+                        },
+                    ]),
+                    ..Default::default()
+                }],
+                substitution_variables: Some(
+                    [("region".to_owned(), vec!["us".to_owned(), "de".to_owned()])].into(),
+                ),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let app_id = "ABCDE12345.com.example.app";
+        let handles = |url| file.handles_url(app_id, url).unwrap();
+        assert!(handles("https://example.com/buy/us/shoes?ref=mail"));
+        assert!(!handles("https://example.com/buy/fr/shoes?ref=mail"));
+        assert!(!handles("https://example.com/buy/us/shoes"));
+        assert!(!handles(
+            "https://example.com/buy/us/shoes?ref=mail#no_universal_links"
+        ));
+        assert!(handles("https://example.com/HELP/Caf%C3%A9"));
+        assert!(!file
+            .handles_url(
+                "OTHER.com.example.app",
+                "https://example.com/buy/us/a?ref=1"
+            )
+            .unwrap());
+        assert!(file
+            .handles_url(app_id, "http://example.com/buy/us/a?ref=1")
+            .is_err());
+    }
+
+    #[test]
+    fn matches_many_wildcards_without_backtracking() {
+        let pattern = Pattern::new(&format!("/{}b", "*a".repeat(30)), None, true);
+        assert!(!pattern.matches(&format!("/{}", "a".repeat(200))));
+        assert!(pattern.matches(&format!("/{}b", "a".repeat(200))));
+        assert!(Pattern::new("/*?", None, true).matches("/a"));
+        assert!(!Pattern::new("/*?", None, true).matches("/"));
+    }
+
+    #[test]
+    fn matches_legacy_paths_through_entitlements() {
+        let entitlements = Entitlements {
+            networking: Networking {
+                associated_domains: Some(vec![
+                    "applinks:*.example.com".parse().unwrap(),
+                    "webcredentials:example.com".parse().unwrap(),
+                ]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let file = AppSiteAssociation {
+            applinks: Some(AppLinks {
+                apps: Some(vec![]),
+                details: vec![AppLinksDetails {
+                    app_id: Some("ABCDE12345.com.example.app".to_owned()),
+                    paths: Some(vec!["NOT /admin/*".to_owned(), "*".to_owned()]),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let files = [("example.com".to_owned(), file)].into();
+        let opens = |url| {
+            opens_universal_link(&entitlements, "ABCDE12345.com.example.app", url, &files).unwrap()
+        };
+        assert!(opens("https://www.example.com/products/1"));
+        assert!(!opens("https://www.example.com/admin/users"));
+        assert!(!opens("https://example.com/products/1"));
+        assert!(!opens("https://www.example.org/products/1"));
+    }
+}
@@ -0,0 +1,382 @@
+use crate::{
+    entitlements::Entitlements,
+    error::{Error, Result},
+    info_plist::InfoPlist,
+    macho::MachO,
+    openstep,
+    platform::{Platform, Target},
+    provisioning_profile::ProvisioningProfile,
+};
+use std::path::{Path, PathBuf};
+
+/// The kind of a bundle, given by the extension of its directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BundleKind {
+    /// An app, `.app`.
+    App,
+    /// A framework, `.framework`.
+    Framework,
+    /// An app extension, `.appex`.
+    AppExtension,
+    /// A loadable bundle or a resource bundle, `.bundle`.
+    Bundle,
+    /// An XPC service, `.xpc`.
+    XpcService,
+    /// A kernel extension, `.kext`.
+    KernelExtension,
+    /// A system extension, `.systemextension`.
+    SystemExtension,
+    /// A DriverKit driver extension, `.dext`.
+    DriverExtension,
+}
+
+impl BundleKind {
+    const ALL: [Self; 8] = [
+        Self::App,
+        Self::Framework,
+        Self::AppExtension,
+        Self::Bundle,
+        Self::XpcService,
+        Self::KernelExtension,
+        Self::SystemExtension,
+        Self::DriverExtension,
+    ];
+
+    /// Returns the extension of bundles of this kind, without the leading dot.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::App => "app",
+            Self::Framework => "framework",
+            Self::AppExtension => "appex",
+            Self::Bundle => "bundle",
+            Self::XpcService => "xpc",
+            Self::KernelExtension => "kext",
+            Self::SystemExtension => "systemextension",
+            Self::DriverExtension => "dext",
+        }
+    }
+
+    /// Returns the kind of bundles with the given extension.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.extension().eq_ignore_ascii_case(extension))
+    }
+
+    /// Returns the kind of the bundle at `path`, from its extension.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        Self::from_extension(path.as_ref().extension()?.to_str()?)
+    }
+}
+
+/// The directory structure of a bundle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BundleLayout {
+    /// Everything at the root of the bundle, as on iOS, tvOS, watchOS and visionOS.
+    Shallow,
+    /// Everything in the `Contents` directory, as on macOS.
+    Deep,
+    /// Everything in `Versions/Current`, as for macOS frameworks.
+    Versioned,
+}
+
+impl BundleLayout {
+    /// Returns the layout that bundles of the given kind use on a platform.
+    pub fn for_platform(platform: Platform, kind: BundleKind) -> Self {
+        match (platform, kind) {
+            (_, BundleKind::DriverExtension) => Self::Shallow,
+            (Platform::MacOs, BundleKind::Framework) => Self::Versioned,
+            (Platform::MacOs, _) => Self::Deep,
+            _ => Self::Shallow,
+        }
+    }
+
+    /// Returns the layout that bundles of the given kind use when built for a Rust
+    /// target. Mac Catalyst apps are deep, like other macOS apps.
+    pub fn for_target(target: &Target, kind: BundleKind) -> Self {
+        Self::for_platform(target.platform(), kind)
+    }
+
+    /// Returns the directory that holds the bundle contents, relative to the bundle.
+    pub fn contents_dir(&self) -> &'static str {
+        match self {
+            Self::Shallow => "",
+            Self::Deep => "Contents",
+            Self::Versioned => "Versions/Current",
+        }
+    }
+
+    /// Returns the path of the information property list, relative to the bundle.
+    pub fn info_plist_path(&self) -> &'static str {
+        match self {
+            Self::Shallow => "Info.plist",
+            Self::Deep => "Contents/Info.plist",
+            Self::Versioned => "Versions/Current/Resources/Info.plist",
+        }
+    }
+
+    /// Returns the directory that holds the executable, relative to the bundle.
+    pub fn executable_dir(&self) -> &'static str {
+        match self {
+            Self::Deep => "Contents/MacOS",
+            _ => self.contents_dir(),
+        }
+    }
+
+    /// Returns the directory that holds the resources, relative to the bundle.
+    pub fn resources_dir(&self) -> &'static str {
+        match self {
+            Self::Shallow => "",
+            Self::Deep => "Contents/Resources",
+            Self::Versioned => "Versions/Current/Resources",
+        }
+    }
+
+    /// Returns the name of the embedded provisioning profile.
+    pub fn embedded_provisioning_profile(&self) -> &'static str {
+        match self {
+            Self::Shallow => "embedded.mobileprovision",
+            _ => "embedded.provisionprofile",
+        }
+    }
+}
+
+/// A bundle directory on disk.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bundle {
+    /// The path of the bundle directory.
+    pub path: PathBuf,
+    /// The kind of the bundle.
+    pub kind: BundleKind,
+    /// The directory structure of the bundle.
+    pub layout: BundleLayout,
+    /// The platform the bundle is built for.
+    pub platform: Platform,
+    /// The information property list of the bundle.
+    pub info_plist: InfoPlist,
+}
+
+impl Bundle {
+    /// Opens the bundle at `path`, detecting its layout and platform and parsing its
+    /// information property list.
+    ///
+    /// The platform is read from the load commands of the executable. Bundles without
+    /// a readable executable fall back to the layout and the minimum OS version keys
+    /// of the property list.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_owned();
+        let kind = BundleKind::from_path(&path).ok_or_else(|| {
+            Error::InvalidBundle(format!("unknown bundle extension of {}", path.display()))
+        })?;
+        let layout = [
+            BundleLayout::Deep,
+            BundleLayout::Versioned,
+            BundleLayout::Shallow,
+        ]
+        .into_iter()
+        .find(|layout| path.join(layout.info_plist_path()).is_file())
+        .ok_or_else(|| Error::InvalidBundle(format!("no Info.plist in {}", path.display())))?;
+        let info_plist: InfoPlist = openstep::read_file(path.join(layout.info_plist_path()))?;
+        let mut bundle = Self {
+            path,
+            kind,
+            layout,
+            platform: Platform::MacOs,
+            info_plist,
+        };
+        bundle.platform = bundle.detect_platform();
+        Ok(bundle)
+    }
+
+    fn detect_platform(&self) -> Platform {
+        let executable = self
+            .executable_path()
+            .and_then(|path| std::fs::read(path).ok());
+        let platform = executable.as_deref().and_then(|data| {
+            MachO::parse(data)
+                .ok()?
+                .iter()
+                .flat_map(|slice| &slice.build_versions)
+                .find_map(|build_version| build_version.platform())
+        });
+        if let Some(platform) = platform {
+            return platform;
+        }
+        let os_version = &self.info_plist.operating_system_version;
+        match self.layout {
+            BundleLayout::Deep | BundleLayout::Versioned => Platform::MacOs,
+            _ if os_version.watch_kit_app == Some(true) => Platform::WatchOs,
+            _ if os_version.minimum_os_version.is_none()
+                && os_version.minimum_system_version.is_some() =>
+            {
+                Platform::MacOs
+            }
+            _ => Platform::Ios,
+        }
+    }
+
+    /// Returns the directory that holds the bundle contents.
+    pub fn contents_dir(&self) -> PathBuf {
+        self.path.join(self.layout.contents_dir())
+    }
+
+    /// Returns the path of the information property list.
+    pub fn info_plist_path(&self) -> PathBuf {
+        self.path.join(self.layout.info_plist_path())
+    }
+
+    /// Returns the path of the executable named by `CFBundleExecutable`, if set.
+    pub fn executable_path(&self) -> Option<PathBuf> {
+        let name = self.info_plist.launch.bundle_executable.as_ref()?;
+        Some(self.path.join(self.layout.executable_dir()).join(name))
+    }
+
+    /// Returns the directory that holds the resources.
+    pub fn resources_dir(&self) -> PathBuf {
+        self.path.join(self.layout.resources_dir())
+    }
+
+    /// Returns the directory that holds the embedded frameworks.
+    pub fn frameworks_dir(&self) -> PathBuf {
+        self.contents_dir().join("Frameworks")
+    }
+
+    /// Returns the directory that holds the embedded app extensions and plug-ins.
+    pub fn plugins_dir(&self) -> PathBuf {
+        self.contents_dir().join("PlugIns")
+    }
+
+    /// Returns the `_CodeSignature` directory, if the bundle is signed.
+    pub fn code_signature_dir(&self) -> Option<PathBuf> {
+        let path = self.contents_dir().join("_CodeSignature");
+        path.is_dir().then_some(path)
+    }
+
+    /// Returns the path of the embedded provisioning profile, if the bundle has one.
+    pub fn embedded_provisioning_profile_path(&self) -> Option<PathBuf> {
+        let path = self
+            .contents_dir()
+            .join(self.layout.embedded_provisioning_profile());
+        path.is_file().then_some(path)
+    }
+
+    /// Parses the embedded provisioning profile, if the bundle has one.
+    pub fn provisioning_profile(&self) -> Result<Option<ProvisioningProfile>> {
+        self.embedded_provisioning_profile_path()
+            .map(ProvisioningProfile::from_file)
+            .transpose()
+    }
+
+    /// Returns the path of the entitlements file that Xcode archives in iOS apps, if
+    /// the bundle has one.
+    pub fn entitlements_file_path(&self) -> Option<PathBuf> {
+        let path = self
+            .contents_dir()
+            .join("archived-expanded-entitlements.xcent");
+        path.is_file().then_some(path)
+    }
+
+    /// Reads the entitlements of the bundle from the code signature of the executable,
+    /// falling back to the archived entitlements file.
+    pub fn entitlements(&self) -> Result<Option<Entitlements>> {
+        if let Some(path) = self.executable_path().filter(|path| path.is_file()) {
+            let data = std::fs::read(path)?;
+            for slice in MachO::parse(&data)? {
+                if let Some(entitlements) = slice.entitlements()? {
+                    return Ok(Some(entitlements));
+                }
+            }
+        }
+        match self.entitlements_file_path() {
+            Some(path) => Ok(Some(openstep::read_file(path)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    fn write_info_plist(path: &Path, info_plist: &InfoPlist) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        plist::to_file_xml(path, info_plist).unwrap();
+    }
+
+    #[test]
+    fn opens_shallow_and_deep_bundles() {
+        let dir = tempfile::tempdir().unwrap();
+        let info_plist = InfoPlist {
+            launch: Launch {
+                bundle_executable: Some("Example".to_owned()),
+                ..Default::default()
+            },
+            operating_system_version: OperatingSystemVersion {
+                minimum_os_version: Some("15.0".to_owned()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let ios = dir.path().join("Example.app");
+        write_info_plist(&ios.join("Info.plist"), &info_plist);
+        let entitlements = Entitlements {
+            code_signing: CodeSigning {
+                get_task_allow: Some(true),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        plist::to_file_xml(
+            ios.join("archived-expanded-entitlements.xcent"),
+            &entitlements,
+        )
+        .unwrap();
+        let bundle = Bundle::open(&ios).unwrap();
+        assert_eq!(bundle.kind, BundleKind::App);
+        assert_eq!(bundle.layout, BundleLayout::Shallow);
+        assert_eq!(bundle.platform, Platform::Ios);
+        assert_eq!(bundle.info_plist, info_plist);
+        assert_eq!(bundle.executable_path(), Some(ios.join("Example")));
+        assert_eq!(bundle.code_signature_dir(), None);
+        assert_eq!(bundle.entitlements().unwrap(), Some(entitlements));
+
+        let macos = dir.path().join("Example.systemextension");
+        write_info_plist(&macos.join("Contents/Info.plist"), &info_plist);
+        std::fs::create_dir_all(macos.join("Contents/_CodeSignature")).unwrap();
+        let bundle = Bundle::open(&macos).unwrap();
+        assert_eq!(bundle.kind, BundleKind::SystemExtension);
+        assert_eq!(bundle.layout, BundleLayout::Deep);
+        assert_eq!(bundle.platform, Platform::MacOs);
+        assert_eq!(
+            bundle.executable_path(),
+            Some(macos.join("Contents/MacOS/Example"))
+        );
+        assert_eq!(bundle.resources_dir(), macos.join("Contents/Resources"));
+        assert_eq!(
+            bundle.code_signature_dir(),
+            Some(macos.join("Contents/_CodeSignature"))
+        );
+        assert!(bundle.provisioning_profile().unwrap().is_none());
+    }
+
+    #[test]
+    fn opens_versioned_frameworks() {
+        let dir = tempfile::tempdir().unwrap();
+        let framework = dir.path().join("Example.framework");
+        write_info_plist(
+            &framework.join("Versions/Current/Resources/Info.plist"),
+            &InfoPlist::default(),
+        );
+        let bundle = Bundle::open(&framework).unwrap();
+        assert_eq!(bundle.kind, BundleKind::Framework);
+        assert_eq!(bundle.layout, BundleLayout::Versioned);
+        assert_eq!(
+            bundle.frameworks_dir(),
+            framework.join("Versions/Current/Frameworks")
+        );
+
+        assert!(Bundle::open(dir.path().join("Missing.app")).is_err());
+        assert!(Bundle::open(dir.path()).is_err());
+    }
+}
@@ -0,0 +1,25 @@
+//! # Bundle.
+//!
+//! The directory structure around the information property list.
+//!
+//! A bundle is a directory with a known extension, like `.app` or `.framework`, holding
+//! an executable, its resources and its information property list. iOS, tvOS,
+//! watchOS and visionOS bundles are shallow: everything lives at the root of the
+//! directory. macOS bundles are deep: everything lives in the `Contents` directory,
+//! with the executable in `Contents/MacOS` and the resources in `Contents/Resources`.
+//! macOS frameworks are versioned, with each version in `Versions/<name>` and
+//! `Versions/Current` linking to the active one.
+//!
+//! Official documentation: <https://developer.apple.com/library/archive/documentation/CoreFoundation/Conceptual/CFBundles/BundleTypes/BundleTypes.html>
+
+pub mod directory;
+pub mod nested;
+pub mod pkg_info;
+pub mod writer;
+
+pub mod prelude {
+    pub use super::directory::*;
+    pub use super::nested::*;
+    pub use super::pkg_info::*;
+    pub use super::writer::*;
+}
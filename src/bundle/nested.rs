@@ -0,0 +1,436 @@
+use super::directory::{Bundle, BundleKind};
+use crate::error::Result;
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+/// A directory of a bundle that holds nested bundles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NestedLocation {
+    /// Embedded frameworks, `Frameworks`.
+    Frameworks,
+    /// App extensions and plug-ins, `PlugIns`.
+    PlugIns,
+    /// ExtensionKit extensions, `Extensions`.
+    Extensions,
+    /// The watchOS app of an iOS app, `Watch`.
+    Watch,
+    /// The App Clip of an iOS app, `AppClips`.
+    AppClips,
+    /// XPC services, `XPCServices`.
+    XpcServices,
+    /// Login items, `Library/LoginItems`.
+    LoginItems,
+    /// System extensions, `Library/SystemExtensions`.
+    SystemExtensions,
+}
+
+impl NestedLocation {
+    pub(crate) const ALL: [Self; 8] = [
+        Self::Frameworks,
+        Self::PlugIns,
+        Self::Extensions,
+        Self::Watch,
+        Self::AppClips,
+        Self::XpcServices,
+        Self::LoginItems,
+        Self::SystemExtensions,
+    ];
+
+    /// Returns the directory, relative to the contents directory of the host bundle.
+    pub fn dir(&self) -> &'static str {
+        match self {
+            Self::Frameworks => "Frameworks",
+            Self::PlugIns => "PlugIns",
+            Self::Extensions => "Extensions",
+            Self::Watch => "Watch",
+            Self::AppClips => "AppClips",
+            Self::XpcServices => "XPCServices",
+            Self::LoginItems => "Library/LoginItems",
+            Self::SystemExtensions => "Library/SystemExtensions",
+        }
+    }
+}
+
+/// A bundle embedded in another bundle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NestedBundle {
+    /// The directory of the host bundle that holds the bundle.
+    pub location: NestedLocation,
+    /// The nested bundle.
+    pub bundle: Bundle,
+}
+
+impl Bundle {
+    /// Opens the bundles directly nested in this bundle, sorted by location and path.
+    pub fn nested_bundles(&self) -> Result<Vec<NestedBundle>> {
+        let mut nested = Vec::new();
+        for location in NestedLocation::ALL {
+            let dir = self.contents_dir().join(location.dir());
+            if !dir.is_dir() {
+                continue;
+            }
+            let mut paths = std::fs::read_dir(dir)?
+                .map(|entry| Ok(entry?.path()))
+                .collect::<Result<Vec<_>>>()?;
+            paths.sort();
+            for path in paths {
+                if path.is_dir() && BundleKind::from_path(&path).is_some() {
+                    let bundle = Bundle::open(path)?;
+                    nested.push(NestedBundle { location, bundle });
+                }
+            }
+        }
+        Ok(nested)
+    }
+}
+
+/// An inconsistency between a nested bundle and its host bundle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NestedBundleMismatch {
+    /// The bundle identifier of the nested bundle isn't prefixed by the bundle
+    /// identifier of the host.
+    BundleIdentifierPrefix {
+        path: PathBuf,
+        host: String,
+        nested: String,
+    },
+    /// `CFBundleShortVersionString` or `CFBundleVersion` of the nested bundle differs
+    /// from the host.
+    Version {
+        path: PathBuf,
+        key: &'static str,
+        host: Option<String>,
+        nested: Option<String>,
+    },
+    /// `WKCompanionAppBundleIdentifier` of a watchOS app doesn't name the iOS app that
+    /// embeds it.
+    CompanionAppBundleIdentifier {
+        path: PathBuf,
+        host: String,
+        nested: Option<String>,
+    },
+    /// `com.apple.developer.parent-application-identifiers` of an App Clip doesn't
+    /// name the app that embeds it.
+    ParentApplicationIdentifiers {
+        path: PathBuf,
+        host: String,
+        nested: Vec<String>,
+    },
+}
+
+impl fmt::Display for NestedBundleMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BundleIdentifierPrefix { path, host, nested } => write!(
+                f,
+                "{}: bundle identifier `{}` is not prefixed by `{}.`",
+                path.display(),
+                nested,
+                host
+            ),
+            Self::Version {
+                path,
+                key,
+                host,
+                nested,
+            } => write!(
+                f,
+                "{}: {} is {:?}, but the host has {:?}",
+                path.display(),
+                key,
+                nested,
+                host
+            ),
+            Self::CompanionAppBundleIdentifier { path, host, nested } => write!(
+                f,
+                "{}: WKCompanionAppBundleIdentifier is {:?}, expected `{}`",
+                path.display(),
+                nested,
+                host
+            ),
+            Self::ParentApplicationIdentifiers { path, host, nested } => write!(
+                f,
+                "{}: parent application identifiers {:?} don't name `{}`",
+                path.display(),
+                nested,
+                host
+            ),
+        }
+    }
+}
+
+/// Checks every bundle nested in `host`, recursively, against the bundle that embeds
+/// it.
+///
+/// App extensions, watchOS apps and App Clips must have a bundle identifier prefixed by
+/// the one of their host and the same version. A watchOS app must name its host as
+/// companion app, and an App Clip as parent application. Other nested bundles, like
+/// frameworks, login items and system extensions, aren't checked.
+pub fn check_nested_bundles(host: &Bundle) -> Result<Vec<NestedBundleMismatch>> {
+    let mut mismatches = Vec::new();
+    check_recursively(&host.path, host, &mut mismatches)?;
+    Ok(mismatches)
+}
+
+fn check_recursively(
+    root: &Path,
+    host: &Bundle,
+    mismatches: &mut Vec<NestedBundleMismatch>,
+) -> Result<()> {
+    for nested in host.nested_bundles()? {
+        check_nested_bundle(root, host, &nested, mismatches)?;
+        check_recursively(root, &nested.bundle, mismatches)?;
+    }
+    Ok(())
+}
+
+fn check_nested_bundle(
+    root: &Path,
+    host: &Bundle,
+    nested: &NestedBundle,
+    mismatches: &mut Vec<NestedBundleMismatch>,
+) -> Result<()> {
+    let bundle = &nested.bundle;
+    let path = relative_path(root, &bundle.path);
+    let host_id = &host.info_plist.identification.bundle_identifier;
+    // Only app extensions, watchOS apps and App Clips share the identity of their host.
+    // Login items, XPC services and system extensions are separate products.
+    let shares_identity = bundle.kind == BundleKind::AppExtension
+        || (nested.location == NestedLocation::Watch && bundle.kind == BundleKind::App)
+        || nested.location == NestedLocation::AppClips;
+    if shares_identity {
+        let nested_id = &bundle.info_plist.identification.bundle_identifier;
+        let is_prefixed = nested_id
+            .strip_prefix(host_id.as_str())
+            .is_some_and(|rest| rest.starts_with('.'));
+        if !is_prefixed {
+            mismatches.push(NestedBundleMismatch::BundleIdentifierPrefix {
+                path: path.clone(),
+                host: host_id.clone(),
+                nested: nested_id.clone(),
+            });
+        }
+        let (host_version, nested_version) = (
+            &host.info_plist.bundle_version,
+            &bundle.info_plist.bundle_version,
+        );
+        for (key, host, nested) in [
+            (
+                "CFBundleShortVersionString",
+                &host_version.bundle_short_version_string,
+                &nested_version.bundle_short_version_string,
+            ),
+            (
+                "CFBundleVersion",
+                &host_version.bundle_version,
+                &nested_version.bundle_version,
+            ),
+        ] {
+            if host != nested {
+                mismatches.push(NestedBundleMismatch::Version {
+                    path: path.clone(),
+                    key,
+                    host: host.clone(),
+                    nested: nested.clone(),
+                });
+            }
+        }
+    }
+
+    match nested.location {
+        NestedLocation::Watch => {
+            let companion = &bundle
+                .info_plist
+                .identification
+                .companion_app_bundle_identifier;
+            if companion.as_ref() != Some(host_id) {
+                mismatches.push(NestedBundleMismatch::CompanionAppBundleIdentifier {
+                    path,
+                    host: host_id.clone(),
+                    nested: companion.clone(),
+                });
+            }
+        }
+        NestedLocation::AppClips => {
+            let parents = bundle
+                .entitlements()?
+                .and_then(|entitlements| entitlements.app_clips.parent_application_identifiers)
+                .unwrap_or_default();
+            // Application identifiers are `<team identifier>.<bundle identifier>`.
+            let names_host = parents
+                .iter()
+                .any(|parent| parent.split_once('.').map(|(_, id)| id) == Some(host_id));
+            if !names_host {
+                mismatches.push(NestedBundleMismatch::ParentApplicationIdentifiers {
+                    path,
+                    host: host_id.clone(),
+                    nested: parents,
+                });
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Returns the path of a nested bundle starting with the name of the outermost bundle.
+fn relative_path(root: &Path, nested: &Path) -> PathBuf {
+    let name = root.file_name().map(Path::new).unwrap_or(root);
+    match nested.strip_prefix(root) {
+        Ok(path) => name.join(path),
+        Err(_) => nested.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{entitlements::app_clips::AppClips, prelude::*};
+
+    fn write_bundle(path: &Path, bundle_identifier: &str, version: &str) -> InfoPlist {
+        let info_plist = InfoPlist {
+            identification: Identification {
+                bundle_identifier: bundle_identifier.to_owned(),
+                ..Default::default()
+            },
+            bundle_version: BundleVersion {
+                bundle_short_version_string: Some("1.0".to_owned()),
+                bundle_version: Some(version.to_owned()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        std::fs::create_dir_all(path).unwrap();
+        plist::to_file_xml(path.join("Info.plist"), &info_plist).unwrap();
+        info_plist
+    }
+
+    #[test]
+    fn checks_nested_bundles_against_host() {
+        let dir = tempfile::tempdir().unwrap();
+        let app = dir.path().join("Example.app");
+        write_bundle(&app, "com.example.app", "7");
+        write_bundle(
+            &app.join("PlugIns/Widget.appex"),
+            "com.example.app.widget",
+            "7",
+        );
+        write_bundle(&app.join("PlugIns/Other.appex"), "com.example.other", "8");
+        write_bundle(
+            &app.join("Frameworks/Kit.framework"),
+            "com.example.kit",
+            "1",
+        );
+        let mut watch = write_bundle(&app.join("Watch/Watch.app"), "com.example.app.watch", "7");
+        watch.identification.companion_app_bundle_identifier = Some("com.example.app".to_owned());
+        plist::to_file_xml(app.join("Watch/Watch.app/Info.plist"), &watch).unwrap();
+        let clip = app.join("AppClips/Clip.app");
+        write_bundle(&clip, "com.example.app.clip", "7");
+        let entitlements = Entitlements {
+            app_clips: AppClips {
+                parent_application_identifiers: Some(vec!["TEAM.com.example.other".to_owned()]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        plist::to_file_xml(
+            clip.join("archived-expanded-entitlements.xcent"),
+            &entitlements,
+        )
+        .unwrap();
+
+        let bundle = Bundle::open(&app).unwrap();
+        let nested = bundle.nested_bundles().unwrap();
+        assert_eq!(
+            nested
+                .iter()
+                .map(|nested| nested.location)
+                .collect::<Vec<_>>(),
+            [
+                NestedLocation::Frameworks,
+                NestedLocation::PlugIns,
+                NestedLocation::PlugIns,
+                NestedLocation::Watch,
+                NestedLocation::AppClips,
+            ]
+        );
+        let other = PathBuf::from("Example.app/PlugIns/Other.appex");
+        assert_eq!(
+            check_nested_bundles(&bundle).unwrap(),
+            [
+                NestedBundleMismatch::BundleIdentifierPrefix {
+                    path: other.clone(),
+                    host: "com.example.app".to_owned(),
+                    nested: "com.example.other".to_owned(),
+                },
+                NestedBundleMismatch::Version {
+                    path: other,
+                    key: "CFBundleVersion",
+                    host: Some("7".to_owned()),
+                    nested: Some("8".to_owned()),
+                },
+                NestedBundleMismatch::ParentApplicationIdentifiers {
+                    path: PathBuf::from("Example.app/AppClips/Clip.app"),
+                    host: "com.example.app".to_owned(),
+                    nested: vec!["TEAM.com.example.other".to_owned()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn checks_app_clip_identity() {
+        let dir = tempfile::tempdir().unwrap();
+        let app = dir.path().join("Example.app");
+        write_bundle(&app, "com.example.app", "7");
+        let clip = app.join("AppClips/Clip.app");
+        write_bundle(&clip, "com.example.clip", "8");
+        let entitlements = Entitlements {
+            app_clips: AppClips {
+                parent_application_identifiers: Some(vec!["TEAM.com.example.app".to_owned()]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        plist::to_file_xml(
+            clip.join("archived-expanded-entitlements.xcent"),
+            &entitlements,
+        )
+        .unwrap();
+
+        let path = PathBuf::from("Example.app/AppClips/Clip.app");
+        assert_eq!(
+            check_nested_bundles(&Bundle::open(&app).unwrap()).unwrap(),
+            [
+                NestedBundleMismatch::BundleIdentifierPrefix {
+                    path: path.clone(),
+                    host: "com.example.app".to_owned(),
+                    nested: "com.example.clip".to_owned(),
+                },
+                NestedBundleMismatch::Version {
+                    path,
+                    key: "CFBundleVersion",
+                    host: Some("7".to_owned()),
+                    nested: Some("8".to_owned()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_identity_checks_for_login_items() {
+        let dir = tempfile::tempdir().unwrap();
+        let app = dir.path().join("Example.app");
+        write_bundle(&app, "com.example.app", "7");
+        write_bundle(
+            &app.join("Library/LoginItems/Helper.app"),
+            "com.example.helper",
+            "2",
+        );
+        let bundle = Bundle::open(&app).unwrap();
+        assert_eq!(bundle.nested_bundles().unwrap().len(), 1);
+        assert_eq!(check_nested_bundles(&bundle).unwrap(), []);
+    }
+}
@@ -0,0 +1,200 @@
+use super::directory::{Bundle, BundleKind};
+use crate::{
+    error::{Error, Result},
+    info_plist::{prelude::BundlePackageType, InfoPlist},
+};
+use std::fmt;
+
+/// The contents of the `PkgInfo` file of a bundle: the package type followed by the
+/// creator code, as two four-character codes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PkgInfo {
+    /// The package type, like `APPL`.
+    pub package_type: BundlePackageType,
+    /// The creator code, `????` when the bundle has none.
+    pub signature: String,
+}
+
+impl PkgInfo {
+    /// Returns the `PkgInfo` of a bundle of the given kind from `CFBundlePackageType`
+    /// and `CFBundleSignature`.
+    ///
+    /// A missing package type is derived from the bundle extension and a missing
+    /// signature is `????`.
+    pub fn new(info_plist: &InfoPlist, kind: BundleKind) -> Self {
+        let categorization = &info_plist.categorization;
+        let package_type = categorization
+            .bundle_package_type
+            .clone()
+            .or_else(|| BundlePackageType::from_extension(kind.extension()))
+            .unwrap_or(BundlePackageType::App);
+        let signature = categorization
+            .bundle_signature
+            .clone()
+            .unwrap_or_else(|| "????".to_owned());
+        Self {
+            package_type,
+            signature,
+        }
+    }
+
+    /// Parses the contents of a `PkgInfo` file.
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        if data.len() != 8 || !data.is_ascii() {
+            return Err(Error::InvalidBundle(format!(
+                "PkgInfo is not two four-character codes: {:?}",
+                String::from_utf8_lossy(data)
+            )));
+        }
+        let (package_type, signature) = data.split_at(4);
+        Ok(Self {
+            package_type: String::from_utf8_lossy(package_type)
+                .parse()
+                .unwrap_or_else(|never| match never {}),
+            signature: String::from_utf8_lossy(signature).into_owned(),
+        })
+    }
+
+    /// Returns the 8 bytes of the `PkgInfo` file.
+    pub fn to_bytes(&self) -> Result<[u8; 8]> {
+        let mut bytes = [0; 8];
+        for (code, bytes) in [self.package_type.code(), &self.signature]
+            .into_iter()
+            .zip(bytes.chunks_mut(4))
+        {
+            if code.len() != 4 || !code.is_ascii() {
+                return Err(Error::InvalidBundle(format!(
+                    "`{}` is not a four-character code",
+                    code
+                )));
+            }
+            bytes.copy_from_slice(code.as_bytes());
+        }
+        Ok(bytes)
+    }
+}
+
+impl fmt::Display for PkgInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.package_type, self.signature)
+    }
+}
+
+/// An inconsistency between the package type of a bundle, its extension and its
+/// `PkgInfo` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PackageTypeMismatch {
+    /// `CFBundlePackageType` differs from the package type of the bundle extension.
+    Extension {
+        extension: &'static str,
+        expected: BundlePackageType,
+        info_plist: BundlePackageType,
+    },
+    /// The `PkgInfo` file differs from `CFBundlePackageType` and `CFBundleSignature`.
+    PkgInfo { expected: PkgInfo, found: PkgInfo },
+}
+
+impl fmt::Display for PackageTypeMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Extension {
+                extension,
+                expected,
+                info_plist,
+            } => write!(
+                f,
+                "CFBundlePackageType is `{}`, but `.{}` bundles are `{}`",
+                info_plist, extension, expected
+            ),
+            Self::PkgInfo { expected, found } => write!(
+                f,
+                "PkgInfo is `{}`, but the Info.plist gives `{}`",
+                found, expected
+            ),
+        }
+    }
+}
+
+/// Checks that `CFBundlePackageType` matches the bundle extension and that the
+/// `PkgInfo` file, if the bundle has one, matches the information property list.
+pub fn check_package_type(bundle: &Bundle) -> Result<Vec<PackageTypeMismatch>> {
+    let mut mismatches = Vec::new();
+    let expected = PkgInfo::new(&bundle.info_plist, bundle.kind);
+    if let Some(package_type) = BundlePackageType::from_extension(bundle.kind.extension()) {
+        if package_type != expected.package_type {
+            mismatches.push(PackageTypeMismatch::Extension {
+                extension: bundle.kind.extension(),
+                expected: package_type,
+                info_plist: expected.package_type.clone(),
+            });
+        }
+    }
+    let path = bundle.contents_dir().join("PkgInfo");
+    if path.is_file() {
+        let found = PkgInfo::parse(&std::fs::read(path)?)?;
+        if found != expected {
+            mismatches.push(PackageTypeMismatch::PkgInfo { expected, found });
+        }
+    }
+    Ok(mismatches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bundle::writer::BundleWriter, platform::Platform, prelude::*};
+
+    #[test]
+    fn generates_and_parses_pkg_info() {
+        let mut info_plist = InfoPlist::default();
+        let pkg_info = PkgInfo::new(&info_plist, BundleKind::AppExtension);
+        assert_eq!(pkg_info.to_bytes().unwrap(), *b"XPC!????");
+        info_plist.categorization.bundle_package_type = Some(BundlePackageType::App);
+        info_plist.categorization.bundle_signature = Some("EXMP".to_owned());
+        let pkg_info = PkgInfo::new(&info_plist, BundleKind::App);
+        assert_eq!(pkg_info.to_bytes().unwrap(), *b"APPLEXMP");
+        assert_eq!(PkgInfo::parse(b"APPLEXMP").unwrap(), pkg_info);
+        assert_eq!(
+            PkgInfo::parse(b"abcd1234").unwrap().package_type,
+            BundlePackageType::Custom("abcd".to_owned())
+        );
+        assert!(PkgInfo::parse(b"APPL").is_err());
+        info_plist.categorization.bundle_signature = Some("toolong".to_owned());
+        assert!(PkgInfo::new(&info_plist, BundleKind::App)
+            .to_bytes()
+            .is_err());
+    }
+
+    #[test]
+    fn checks_package_type() {
+        let dir = tempfile::tempdir().unwrap();
+        let executable = dir.path().join("Example");
+        std::fs::write(&executable, b"binary").unwrap();
+        let info_plist = InfoPlist {
+            categorization: Categorization {
+                bundle_package_type: Some(BundlePackageType::Bundle),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let bundle = BundleWriter::new(info_plist, &executable, Platform::Ios)
+            .write(dir.path().join("Example.app"))
+            .unwrap();
+        assert_eq!(
+            check_package_type(&bundle).unwrap(),
+            [PackageTypeMismatch::Extension {
+                extension: "app",
+                expected: BundlePackageType::App,
+                info_plist: BundlePackageType::Bundle,
+            }]
+        );
+
+        std::fs::write(dir.path().join("Example.app/PkgInfo"), b"APPL????").unwrap();
+        let mismatches = check_package_type(&bundle).unwrap();
+        assert_eq!(mismatches.len(), 2);
+        assert_eq!(
+            mismatches[1].to_string(),
+            "PkgInfo is `APPL????`, but the Info.plist gives `BNDL????`"
+        );
+    }
+}
@@ -0,0 +1,280 @@
+use super::{
+    directory::{Bundle, BundleKind, BundleLayout},
+    pkg_info::PkgInfo,
+};
+use crate::{
+    error::{Error, Result},
+    info_plist::InfoPlist,
+    platform::Platform,
+};
+use std::path::{Path, PathBuf};
+
+/// Assembles a bundle directory from an executable, its information property list and
+/// its resources.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BundleWriter {
+    /// The information property list to write. `CFBundleExecutable` defaults to the
+    /// file name of the executable.
+    pub info_plist: InfoPlist,
+    /// The executable to copy into the bundle.
+    pub executable: PathBuf,
+    /// The platform that decides the layout of the bundle.
+    pub platform: Platform,
+    /// Files and directories to copy into the resources directory.
+    pub resources: Vec<PathBuf>,
+    /// Framework bundles to copy into the `Frameworks` directory.
+    pub frameworks: Vec<PathBuf>,
+    /// App extensions and plug-ins to copy into the `PlugIns` directory.
+    pub plugins: Vec<PathBuf>,
+}
+
+impl BundleWriter {
+    /// Creates a writer for `executable` without resources, frameworks or plug-ins.
+    pub fn new(info_plist: InfoPlist, executable: impl Into<PathBuf>, platform: Platform) -> Self {
+        Self {
+            info_plist,
+            executable: executable.into(),
+            platform,
+            resources: Vec::new(),
+            frameworks: Vec::new(),
+            plugins: Vec::new(),
+        }
+    }
+
+    /// Writes the bundle to `path`, whose extension decides the kind of the bundle,
+    /// and opens it as a bundle for [`BundleWriter::platform`].
+    ///
+    /// Existing files in the bundle are overwritten, other files are left untouched.
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<Bundle> {
+        let path = path.as_ref();
+        let kind = BundleKind::from_path(path).ok_or_else(|| {
+            Error::InvalidBundle(format!("unknown bundle extension of {}", path.display()))
+        })?;
+        let file_name = self
+            .executable
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| {
+                Error::InvalidBundle(format!("invalid executable {}", self.executable.display()))
+            })?;
+        let mut info_plist = self.info_plist.clone();
+        let executable_name = info_plist
+            .launch
+            .bundle_executable
+            .get_or_insert_with(|| file_name.to_owned())
+            .clone();
+        if executable_name != file_name {
+            return Err(Error::InvalidBundle(format!(
+                "CFBundleExecutable `{}` doesn't match the executable `{}`",
+                executable_name, file_name
+            )));
+        }
+
+        let layout = BundleLayout::for_platform(self.platform, kind);
+        if layout == BundleLayout::Versioned {
+            std::fs::create_dir_all(path.join("Versions/A/Resources"))?;
+            for (link, target) in [
+                ("Versions/Current", "A".to_owned()),
+                ("Resources", "Versions/Current/Resources".to_owned()),
+                (
+                    &executable_name,
+                    format!("Versions/Current/{}", executable_name),
+                ),
+            ] {
+                symlink(target, path.join(link))?;
+            }
+        }
+        let executable_dir = path.join(layout.executable_dir());
+        let resources_dir = path.join(layout.resources_dir());
+        let contents_dir = path.join(layout.contents_dir());
+        std::fs::create_dir_all(&executable_dir)?;
+        std::fs::create_dir_all(&resources_dir)?;
+
+        let executable = executable_dir.join(&executable_name);
+        std::fs::copy(&self.executable, &executable)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&executable, std::fs::Permissions::from_mode(0o755))?;
+        }
+        plist::to_file_xml(path.join(layout.info_plist_path()), &info_plist)?;
+        if kind == BundleKind::App {
+            let pkg_info = PkgInfo::new(&info_plist, kind).to_bytes()?;
+            std::fs::write(contents_dir.join("PkgInfo"), pkg_info)?;
+        }
+        for (sources, dir) in [
+            (&self.resources, resources_dir),
+            (&self.frameworks, contents_dir.join("Frameworks")),
+            (&self.plugins, contents_dir.join("PlugIns")),
+        ] {
+            for source in sources {
+                let name = source.file_name().ok_or_else(|| {
+                    Error::InvalidBundle(format!("invalid bundle input {}", source.display()))
+                })?;
+                copy(source, &dir.join(name))?;
+            }
+        }
+        let mut bundle = Bundle::open(path)?;
+        bundle.platform = self.platform;
+        Ok(bundle)
+    }
+}
+
+/// Copies a file or a directory tree, preserving symbolic links.
+fn copy(from: &Path, to: &Path) -> Result<()> {
+    let metadata = std::fs::symlink_metadata(from)?;
+    if metadata.file_type().is_symlink() {
+        if std::fs::symlink_metadata(to).is_ok() {
+            std::fs::remove_file(to)?;
+        }
+        symlink(std::fs::read_link(from)?, to.to_owned())?;
+    } else if metadata.is_dir() {
+        std::fs::create_dir_all(to)?;
+        for entry in std::fs::read_dir(from)? {
+            let entry = entry?;
+            copy(&entry.path(), &to.join(entry.file_name()))?;
+        }
+    } else {
+        if let Some(parent) = to.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::copy(from, to)?;
+    }
+    Ok(())
+}
+
+/// Creates a symbolic link, replacing a link to another target.
+fn symlink(target: impl Into<PathBuf>, link: PathBuf) -> Result<()> {
+    let target = target.into();
+    if let Ok(metadata) = std::fs::symlink_metadata(&link) {
+        if !metadata.file_type().is_symlink() {
+            return Err(Error::InvalidBundle(format!(
+                "{} exists and isn't a symbolic link",
+                link.display()
+            )));
+        }
+        if std::fs::read_link(&link)? == target {
+            return Ok(());
+        }
+        #[cfg(unix)]
+        std::fs::remove_file(&link)?;
+        #[cfg(windows)]
+        {
+            use std::os::windows::fs::FileTypeExt;
+            match metadata.file_type().is_symlink_dir() {
+                true => std::fs::remove_dir(&link)?,
+                false => std::fs::remove_file(&link)?,
+            }
+        }
+    }
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(target, link)?;
+    #[cfg(windows)]
+    {
+        // Windows links to files and to directories differently. Relative targets are
+        // resolved from the directory of the link.
+        let resolved = match link.parent() {
+            Some(parent) => parent.join(&target),
+            None => target.clone(),
+        };
+        match resolved.is_dir() {
+            true => std::os::windows::fs::symlink_dir(target, link)?,
+            false => std::os::windows::fs::symlink_file(target, link)?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn writes_macos_and_ios_apps() {
+        let dir = tempfile::tempdir().unwrap();
+        let executable = dir.path().join("example");
+        std::fs::write(&executable, b"binary").unwrap();
+        let resources = dir.path().join("assets");
+        std::fs::create_dir_all(resources.join("images")).unwrap();
+        std::fs::write(resources.join("images/icon.png"), b"png").unwrap();
+
+        let mut writer = BundleWriter::new(InfoPlist::default(), &executable, Platform::MacOs);
+        writer.resources.push(resources);
+        let bundle = writer.write(dir.path().join("Example.app")).unwrap();
+        let contents = dir.path().join("Example.app/Contents");
+        assert_eq!(bundle.layout, BundleLayout::Deep);
+        assert_eq!(
+            bundle.info_plist.launch.bundle_executable.as_deref(),
+            Some("example")
+        );
+        assert_eq!(
+            std::fs::read(contents.join("MacOS/example")).unwrap(),
+            b"binary"
+        );
+        assert_eq!(
+            std::fs::read(contents.join("PkgInfo")).unwrap(),
+            b"APPL????"
+        );
+        assert!(contents.join("Resources/assets/images/icon.png").is_file());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let metadata = std::fs::metadata(contents.join("MacOS/example")).unwrap();
+            assert_eq!(metadata.permissions().mode() & 0o777, 0o755);
+        }
+
+        let bundle = BundleWriter::new(InfoPlist::default(), &executable, Platform::Ios)
+            .write(dir.path().join("Example-iOS.app"))
+            .unwrap();
+        assert_eq!(bundle.layout, BundleLayout::Shallow);
+        assert_eq!(bundle.platform, Platform::Ios);
+        assert!(dir.path().join("Example-iOS.app/example").is_file());
+        assert!(dir.path().join("Example-iOS.app/PkgInfo").is_file());
+
+        let bundle = BundleWriter::new(InfoPlist::default(), &executable, Platform::TvOs)
+            .write(dir.path().join("Example-tvOS.app"))
+            .unwrap();
+        assert_eq!(bundle.platform, Platform::TvOs);
+
+        let info_plist = InfoPlist {
+            launch: Launch {
+                bundle_executable: Some("Other".to_owned()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let writer = BundleWriter::new(info_plist, &executable, Platform::Ios);
+        assert!(writer.write(dir.path().join("Other.app")).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn writes_versioned_frameworks() {
+        let dir = tempfile::tempdir().unwrap();
+        let executable = dir.path().join("Example");
+        std::fs::write(&executable, b"binary").unwrap();
+        let bundle = BundleWriter::new(InfoPlist::default(), &executable, Platform::MacOs)
+            .write(dir.path().join("Example.framework"))
+            .unwrap();
+        let framework = dir.path().join("Example.framework");
+        assert_eq!(bundle.layout, BundleLayout::Versioned);
+        assert_eq!(
+            std::fs::read_link(framework.join("Versions/Current")).unwrap(),
+            Path::new("A")
+        );
+        assert!(framework.join("Versions/A/Resources/Info.plist").is_file());
+        assert_eq!(std::fs::read(framework.join("Example")).unwrap(), b"binary");
+        assert!(!framework.join("Versions/A/PkgInfo").exists());
+
+        std::fs::remove_file(framework.join("Versions/Current")).unwrap();
+        std::os::unix::fs::symlink("B", framework.join("Versions/Current")).unwrap();
+        BundleWriter::new(InfoPlist::default(), &executable, Platform::MacOs)
+            .write(&framework)
+            .unwrap();
+        assert_eq!(
+            std::fs::read_link(framework.join("Versions/Current")).unwrap(),
+            Path::new("A")
+        );
+    }
+}
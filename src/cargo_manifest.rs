@@ -0,0 +1,331 @@
+//! # Cargo Manifest.
+//!
+//! Generates the information property list and the entitlements of a Rust app from its
+//! `Cargo.toml`.
+//!
+//! The package name becomes `CFBundleName` and `CFBundleExecutable`, and
+//! `CFBundleIdentifier` with the characters a bundle identifier can't hold, like `_`,
+//! replaced by `-`. The version becomes `CFBundleShortVersionString` and
+//! `CFBundleVersion`, and the authors become `NSHumanReadableCopyright`. Every other
+//! key, and any of these, can be set in the `[package.metadata.apple-bundle]` table:
+//!
+//! ```toml
+//! [package.metadata.apple-bundle.info-plist]
+//! CFBundleIdentifier = "com.example.app"
+//! LSApplicationCategoryType = "public.app-category.developer-tools"
+//!
+//! [package.metadata.apple-bundle.entitlements]
+//! "com.apple.developer.game-center" = true
+//! ```
+
+use crate::{
+    entitlements::Entitlements,
+    error::{Error, Result},
+    info_plist::InfoPlist,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::path::Path;
+use toml::{Table, Value};
+
+/// The name of the metadata table read by [`CargoPackage`].
+pub const METADATA_KEY: &str = "apple-bundle";
+
+/// The package of a `Cargo.toml` and its `[package.metadata.apple-bundle]` table.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CargoPackage {
+    /// The package name.
+    pub name: String,
+    /// The package version, if it isn't inherited from the workspace.
+    pub version: Option<String>,
+    /// The package description. It has no information property list key.
+    pub description: Option<String>,
+    /// The package authors.
+    pub authors: Vec<String>,
+    /// The `[package.metadata.apple-bundle]` table.
+    pub metadata: Table,
+}
+
+/// The information property list and the entitlements built from a [`CargoPackage`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CargoBundle {
+    /// The information property list.
+    pub info_plist: InfoPlist,
+    /// The entitlements.
+    pub entitlements: Entitlements,
+    /// The keys of the metadata table that this crate doesn't model, like
+    /// `info-plist.CFBundleSomething`, relative to the metadata table.
+    pub unknown_keys: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct Manifest {
+    package: Package,
+}
+
+#[derive(Deserialize)]
+struct Package {
+    name: String,
+    version: Option<Inheritable<String>>,
+    description: Option<Inheritable<String>>,
+    authors: Option<Inheritable<Vec<String>>>,
+    #[serde(default)]
+    metadata: Table,
+}
+
+/// A package field, either set or inherited with `field.workspace = true`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Inheritable<T> {
+    Value(T),
+    Workspace {},
+}
+
+impl<T> Inheritable<T> {
+    fn value(self) -> Option<T> {
+        match self {
+            Self::Value(value) => Some(value),
+            Self::Workspace {} => None,
+        }
+    }
+}
+
+impl CargoPackage {
+    /// Reads the `Cargo.toml` at `path`.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Parses the contents of a `Cargo.toml`.
+    ///
+    /// Fields inherited from the workspace are left unset.
+    pub fn parse(manifest: &str) -> Result<Self> {
+        let Manifest { mut package } = toml::from_str(manifest)?;
+        let metadata = match package.metadata.remove(METADATA_KEY) {
+            Some(Value::Table(metadata)) => metadata,
+            Some(_) => {
+                return Err(Error::InvalidManifest(format!(
+                    "package.metadata.{} is not a table",
+                    METADATA_KEY
+                )))
+            }
+            None => Table::new(),
+        };
+        Ok(Self {
+            name: package.name,
+            version: package.version.and_then(Inheritable::value),
+            description: package.description.and_then(Inheritable::value),
+            authors: package
+                .authors
+                .and_then(Inheritable::value)
+                .unwrap_or_default(),
+            metadata,
+        })
+    }
+
+    /// Builds the information property list and the entitlements of the package.
+    ///
+    /// Keys of the metadata table override the values derived from the package.
+    pub fn build(&self) -> Result<CargoBundle> {
+        let mut unknown_keys = Vec::new();
+        for key in self.metadata.keys() {
+            if !matches!(key.as_str(), "info-plist" | "entitlements") {
+                unknown_keys.push(key.clone());
+            }
+        }
+
+        let mut info_plist = Table::new();
+        info_plist.insert("CFBundleName".to_owned(), self.name.clone().into());
+        info_plist.insert("CFBundleExecutable".to_owned(), self.name.clone().into());
+        info_plist.insert(
+            "CFBundleIdentifier".to_owned(),
+            bundle_identifier(&self.name).into(),
+        );
+        if let Some(version) = &self.version {
+            let version = bundle_version(version);
+            info_plist.insert("CFBundleShortVersionString".to_owned(), version.into());
+            info_plist.insert("CFBundleVersion".to_owned(), version.into());
+        }
+        if !self.authors.is_empty() {
+            let authors = self
+                .authors
+                .iter()
+                .map(|author| author.split(" <").next().unwrap_or(author).trim())
+                .collect::<Vec<_>>()
+                .join(", ");
+            info_plist.insert(
+                "NSHumanReadableCopyright".to_owned(),
+                format!("Copyright © {}", authors).into(),
+            );
+        }
+        info_plist.extend(self.metadata_table("info-plist")?);
+        let info_plist: InfoPlist = from_table("info-plist", info_plist, &mut unknown_keys)?;
+
+        let entitlements = self.metadata_table("entitlements")?;
+        let entitlements: Entitlements =
+            from_table("entitlements", entitlements, &mut unknown_keys)?;
+        Ok(CargoBundle {
+            info_plist,
+            entitlements,
+            unknown_keys,
+        })
+    }
+
+    fn metadata_table(&self, key: &str) -> Result<Table> {
+        match self.metadata.get(key) {
+            Some(Value::Table(table)) => Ok(table.clone()),
+            Some(_) => Err(Error::InvalidManifest(format!(
+                "package.metadata.{}.{} is not a table",
+                METADATA_KEY, key
+            ))),
+            None => Ok(Table::new()),
+        }
+    }
+}
+
+/// Returns the package name with the characters other than alphanumerics, `-` and `.`
+/// replaced by `-`.
+fn bundle_identifier(name: &str) -> String {
+    name.chars()
+        .map(|c| match c.is_ascii_alphanumeric() || c == '.' {
+            true => c,
+            false => '-',
+        })
+        .collect()
+}
+
+/// Returns the version without its pre-release and build metadata, as
+/// `CFBundleShortVersionString` only allows period-separated integers.
+fn bundle_version(version: &str) -> &str {
+    version.split(['-', '+']).next().unwrap_or(version)
+}
+
+/// Deserializes `table` and adds the keys that didn't survive a round trip to
+/// `unknown_keys`.
+fn from_table<T: DeserializeOwned + Serialize>(
+    prefix: &str,
+    table: Table,
+    unknown_keys: &mut Vec<String>,
+) -> Result<T> {
+    let table = Value::Table(table);
+    let value = T::deserialize(table.clone())?;
+    let known = Value::try_from(&value)?;
+    collect_unknown_keys(prefix, &table, &known, unknown_keys);
+    Ok(value)
+}
+
+fn collect_unknown_keys(prefix: &str, value: &Value, known: &Value, keys: &mut Vec<String>) {
+    match (value, known) {
+        (Value::Table(table), Value::Table(known)) => {
+            for (key, value) in table {
+                let path = format!("{}.{}", prefix, key);
+                match known.get(key) {
+                    Some(known) => collect_unknown_keys(&path, value, known, keys),
+                    None => keys.push(path),
+                }
+            }
+        }
+        (Value::Array(values), Value::Array(known)) => {
+            for (index, (value, known)) in values.iter().zip(known).enumerate() {
+                collect_unknown_keys(&format!("{}[{}]", prefix, index), value, known, keys);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    const MANIFEST: &str = r#"
+[package]
+name = "example"
+version = "1.2.3-beta.1"
+description = "An example app"
+authors = ["Jane Doe <jane@example.com>", "Example Team"]
+edition.workspace = true
+
+[package.metadata.apple-bundle]
+icon = "icon.png"
+
+[package.metadata.apple-bundle.info-plist]
+CFBundleIdentifier = "com.example.app"
+CFBundleName = "Example"
+LSApplicationCategoryType = "public.app-category.developer-tools"
+CFBundleSomething = "unknown"
+
+[[package.metadata.apple-bundle.info-plist.CFBundleURLTypes]]
+CFBundleURLName = "com.example.app"
+CFBundleURLSchemes = ["example"]
+CFBundleURLUnknown = 1
+
+[package.metadata.apple-bundle.entitlements]
+"com.apple.developer.game-center" = true
+"#;
+
+    #[test]
+    fn builds_info_plist_from_cargo_toml() {
+        let package = CargoPackage::parse(MANIFEST).unwrap();
+        assert_eq!(package.description.as_deref(), Some("An example app"));
+        let bundle = package.build().unwrap();
+        let info_plist = &bundle.info_plist;
+        assert_eq!(
+            info_plist.identification.bundle_identifier,
+            "com.example.app"
+        );
+        assert_eq!(info_plist.naming.bundle_name.as_deref(), Some("Example"));
+        assert_eq!(
+            info_plist.launch.bundle_executable.as_deref(),
+            Some("example")
+        );
+        assert_eq!(
+            info_plist
+                .bundle_version
+                .bundle_short_version_string
+                .as_deref(),
+            Some("1.2.3")
+        );
+        assert_eq!(
+            info_plist
+                .bundle_version
+                .human_readable_copyright
+                .as_deref(),
+            Some("Copyright © Jane Doe, Example Team")
+        );
+        assert_eq!(
+            info_plist.categorization.application_category_type,
+            Some(AppCategoryType::DeveloperTools)
+        );
+        assert_eq!(bundle.entitlements.games.game_center, Some(true));
+        assert_eq!(
+            bundle.unknown_keys,
+            [
+                "icon",
+                "info-plist.CFBundleSomething",
+                "info-plist.CFBundleURLTypes[0].CFBundleURLUnknown",
+            ]
+        );
+    }
+
+    #[test]
+    fn derives_bundle_identifier_from_package_name() {
+        let manifest = "[package]\nname = \"example_app\"\n";
+        let bundle = CargoPackage::parse(manifest).unwrap().build().unwrap();
+        assert_eq!(
+            bundle.info_plist.identification.bundle_identifier,
+            "example-app"
+        );
+        assert!(bundle.unknown_keys.is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_metadata() {
+        let manifest = "[package]\nname = \"example\"\nmetadata.apple-bundle = 1\n";
+        assert!(CargoPackage::parse(manifest).is_err());
+        let manifest = "[package]\nname = \"example\"\n\n\
+                        [package.metadata.apple-bundle.info-plist]\n\
+                        LSApplicationCategoryType = \"unknown\"\n";
+        assert!(CargoPackage::parse(manifest).unwrap().build().is_err());
+    }
+}
@@ -0,0 +1,636 @@
+//! # Code Resources.
+//!
+//! The manifest that seals the resources of a signed bundle.
+//!
+//! `_CodeSignature/CodeResources` is a property list with the hashes of every resource
+//! of a bundle, except the main executable, which its code signature covers directly.
+//! `files` and `rules` are the legacy manifest and its rules, with SHA-1 hashes.
+//! `files2` and `rules2` are the current manifest and its rules, with SHA-1 and
+//! SHA-256 hashes, symbolic links and nested code.
+//!
+//! Each rule maps a regular expression over paths, relative to the contents directory
+//! of the bundle, to whether matching files are sealed. The rule with the highest weight
+//! wins. Omitted files aren't sealed, optional files may be missing, and nested code is
+//! sealed by the hash of its code directory instead of its contents.
+
+use crate::{
+    bundle::directory::{Bundle, BundleKind, BundleLayout},
+    error::{Error, Result},
+    macho::MachO,
+};
+use plist::Data;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use sha2::Sha256;
+use std::{collections::BTreeMap, fmt, path::Path};
+
+/// The contents of `_CodeSignature/CodeResources`.
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq)]
+pub struct CodeResources {
+    /// The legacy manifest, sealed with `rules`.
+    #[serde(default)]
+    pub files: BTreeMap<String, FileHash>,
+    /// The current manifest, sealed with `rules2`.
+    #[serde(default)]
+    pub files2: BTreeMap<String, Resource>,
+    /// The rules of the legacy manifest.
+    #[serde(default)]
+    pub rules: BTreeMap<String, ResourceRule>,
+    /// The rules of the current manifest.
+    #[serde(default)]
+    pub rules2: BTreeMap<String, ResourceRule>,
+}
+
+/// An entry of the legacy manifest.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum FileHash {
+    /// The SHA-1 hash of a required file.
+    Hash(Data),
+    /// The SHA-1 hash of a file, with whether it may be missing.
+    Optional { hash: Data, optional: bool },
+}
+
+impl FileHash {
+    /// Returns the SHA-1 hash of the file.
+    pub fn hash(&self) -> &Data {
+        match self {
+            Self::Hash(hash) | Self::Optional { hash, .. } => hash,
+        }
+    }
+
+    /// Whether the file may be missing.
+    pub fn is_optional(&self) -> bool {
+        matches!(self, Self::Optional { optional: true, .. })
+    }
+}
+
+/// An entry of the current manifest: a file, a symbolic link or nested code.
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq, Eq)]
+pub struct Resource {
+    /// The SHA-1 hash of a file.
+    #[serde(
+        serialize_with = "crate::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub hash: Option<Data>,
+    /// The SHA-256 hash of a file.
+    #[serde(
+        serialize_with = "crate::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub hash2: Option<Data>,
+    /// Whether the file may be missing.
+    #[serde(
+        serialize_with = "crate::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub optional: Option<bool>,
+    /// The target of a symbolic link.
+    #[serde(
+        serialize_with = "crate::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub symlink: Option<String>,
+    /// The code directory hash of nested code.
+    #[serde(
+        serialize_with = "crate::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub cdhash: Option<Data>,
+    /// The designated requirement of nested code.
+    #[serde(
+        serialize_with = "crate::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub requirement: Option<String>,
+}
+
+impl Resource {
+    /// Whether `other` has the same contents, comparing the strongest hash that this
+    /// entry records.
+    pub fn matches(&self, other: &Resource) -> bool {
+        if self.symlink.is_some() || other.symlink.is_some() {
+            return self.symlink == other.symlink;
+        }
+        if self.cdhash.is_some() {
+            return self.cdhash == other.cdhash;
+        }
+        match self.hash2.is_some() {
+            true => self.hash2 == other.hash2,
+            false => self.hash == other.hash,
+        }
+    }
+}
+
+/// A rule of a manifest.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum ResourceRule {
+    /// Whether matching files are sealed, with the default weight.
+    Include(bool),
+    /// How matching files are sealed.
+    Options(ResourceRuleOptions),
+}
+
+/// The options of a rule.
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq)]
+pub struct ResourceRuleOptions {
+    /// Matching files aren't sealed.
+    #[serde(
+        serialize_with = "crate::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub omit: Option<bool>,
+    /// Matching files may be missing.
+    #[serde(
+        serialize_with = "crate::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub optional: Option<bool>,
+    /// Matching files and bundles are nested code.
+    #[serde(
+        serialize_with = "crate::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub nested: Option<bool>,
+    /// The priority of the rule. Defaults to `1`.
+    #[serde(
+        serialize_with = "crate::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub weight: Option<f64>,
+}
+
+impl ResourceRule {
+    fn weight(weight: f64) -> Self {
+        Self::options(weight, false, false, false)
+    }
+
+    fn options(weight: f64, omit: bool, optional: bool, nested: bool) -> Self {
+        Self::Options(ResourceRuleOptions {
+            omit: omit.then_some(true),
+            optional: optional.then_some(true),
+            nested: nested.then_some(true),
+            weight: Some(weight),
+        })
+    }
+}
+
+/// How a rule seals a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Seal {
+    Exclude,
+    Include { optional: bool, nested: bool },
+}
+
+/// Rules compiled to regular expressions.
+struct Rules(Vec<(Regex, f64, Seal)>);
+
+impl Rules {
+    fn new(rules: &BTreeMap<String, ResourceRule>) -> Result<Self> {
+        let mut compiled = Vec::with_capacity(rules.len());
+        for (pattern, rule) in rules {
+            let regex = Regex::new(pattern)
+                .map_err(|err| Error::InvalidResourceRule(format!("{}: {}", pattern, err)))?;
+            let (weight, seal) = match rule {
+                ResourceRule::Include(include) => (
+                    1.0,
+                    match include {
+                        true => Seal::Include {
+                            optional: false,
+                            nested: false,
+                        },
+                        false => Seal::Exclude,
+                    },
+                ),
+                ResourceRule::Options(options) => (
+                    options.weight.unwrap_or(1.0),
+                    match options.omit {
+                        Some(true) => Seal::Exclude,
+                        _ => Seal::Include {
+                            optional: options.optional == Some(true),
+                            nested: options.nested == Some(true),
+                        },
+                    },
+                ),
+            };
+            compiled.push((regex, weight, seal));
+        }
+        Ok(Self(compiled))
+    }
+
+    /// Returns how the rule with the highest weight among the matching rules seals
+    /// `path`.
+    fn seal(&self, path: &str) -> Seal {
+        let mut best: Option<(f64, Seal)> = None;
+        for (regex, weight, seal) in &self.0 {
+            if best.is_none_or(|(best, _)| *weight > best) && regex.is_match(path) {
+                best = Some((*weight, *seal));
+            }
+        }
+        best.map_or(Seal::Exclude, |(_, seal)| seal)
+    }
+}
+
+/// A difference between a manifest and the resources of a bundle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResourceChange {
+    /// A sealed resource that isn't in the manifest.
+    Added(String),
+    /// A required resource of the manifest that is missing.
+    Removed(String),
+    /// A resource whose contents don't match the manifest.
+    Modified(String),
+}
+
+impl fmt::Display for ResourceChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Added(path) => write!(f, "file added: {}", path),
+            Self::Removed(path) => write!(f, "file missing: {}", path),
+            Self::Modified(path) => write!(f, "file modified: {}", path),
+        }
+    }
+}
+
+impl CodeResources {
+    /// Returns an empty manifest with the rules that `codesign` uses by default for
+    /// bundles with the given layout.
+    pub fn with_default_rules(layout: BundleLayout) -> Self {
+        let rules: Vec<(&str, ResourceRule)>;
+        let rules2: Vec<(&str, ResourceRule)>;
+        let optional = ResourceRule::options(1000.0, false, true, false);
+        let omit = |weight| ResourceRule::options(weight, true, false, false);
+        if layout == BundleLayout::Shallow {
+            rules = vec![
+                ("^.*", ResourceRule::Include(true)),
+                ("^.*\\.lproj/", optional.clone()),
+                ("^.*\\.lproj/locversion.plist$", omit(1100.0)),
+                ("^Base\\.lproj/", ResourceRule::weight(1010.0)),
+                ("^version.plist$", ResourceRule::Include(true)),
+            ];
+            rules2 = vec![
+                (".*\\.dSYM($|/)", ResourceRule::weight(11.0)),
+                ("^(.*/)?\\.DS_Store$", omit(2000.0)),
+                ("^.*", ResourceRule::Include(true)),
+                ("^.*\\.lproj/", optional),
+                ("^.*\\.lproj/locversion.plist$", omit(1100.0)),
+                ("^Base\\.lproj/", ResourceRule::weight(1010.0)),
+                ("^Info\\.plist$", omit(20.0)),
+                ("^PkgInfo$", omit(20.0)),
+                ("^embedded\\.provisionprofile$", ResourceRule::weight(20.0)),
+                ("^version\\.plist$", ResourceRule::weight(20.0)),
+            ];
+        } else {
+            let nested = ResourceRule::options(10.0, false, false, true);
+            rules = vec![
+                ("^Resources/", ResourceRule::Include(true)),
+                ("^Resources/.*\\.lproj/", optional.clone()),
+                ("^Resources/.*\\.lproj/locversion.plist$", omit(1100.0)),
+                ("^Resources/Base\\.lproj/", ResourceRule::weight(1010.0)),
+                ("^version.plist$", ResourceRule::Include(true)),
+            ];
+            rules2 = vec![
+                (".*\\.dSYM($|/)", ResourceRule::weight(11.0)),
+                ("^(.*/)?\\.DS_Store$", omit(2000.0)),
+                (
+                    "^(Frameworks|SharedFrameworks|PlugIns|Plug-ins|XPCServices|Helpers|MacOS|Library/(Automator|Spotlight|LoginItems))/",
+                    nested.clone(),
+                ),
+                ("^.*", ResourceRule::Include(true)),
+                ("^Info\\.plist$", omit(20.0)),
+                ("^PkgInfo$", omit(20.0)),
+                ("^Resources/", ResourceRule::weight(20.0)),
+                ("^Resources/.*\\.lproj/", optional),
+                ("^Resources/.*\\.lproj/locversion.plist$", omit(1100.0)),
+                ("^Resources/Base\\.lproj/", ResourceRule::weight(1010.0)),
+                ("^[^/]+$", nested),
+                ("^embedded\\.provisionprofile$", ResourceRule::weight(20.0)),
+                ("^version\\.plist$", ResourceRule::weight(20.0)),
+            ];
+        }
+        let collect = |rules: Vec<(&str, ResourceRule)>| {
+            rules
+                .into_iter()
+                .map(|(pattern, rule)| (pattern.to_owned(), rule))
+                .collect()
+        };
+        Self {
+            rules: collect(rules),
+            rules2: collect(rules2),
+            ..Default::default()
+        }
+    }
+
+    /// Computes the manifest of a bundle with the default rules.
+    pub fn generate(bundle: &Bundle) -> Result<Self> {
+        Self::with_default_rules(bundle.layout).seal(bundle)
+    }
+
+    /// Computes the manifest of a bundle with the rules of this manifest.
+    ///
+    /// Nested code is sealed by its `cdhash` only; its designated requirement isn't
+    /// computed.
+    pub fn seal(&self, bundle: &Bundle) -> Result<Self> {
+        let mut sealed = Self {
+            rules: self.rules.clone(),
+            rules2: self.rules2.clone(),
+            ..Default::default()
+        };
+        let root = bundle.contents_dir();
+        let executable = bundle
+            .executable_path()
+            .and_then(|path| Some(relative_path(path.strip_prefix(&root).ok()?)));
+        let mut sealer = Sealer {
+            rules: Rules::new(&self.rules)?,
+            rules2: Rules::new(&self.rules2)?,
+            excluded: ["_CodeSignature".to_owned(), "CodeResources".to_owned()]
+                .into_iter()
+                .chain(executable)
+                .collect(),
+            manifest: &mut sealed,
+        };
+        sealer.seal_dir(&root, "")?;
+        Ok(sealed)
+    }
+
+    /// Reads the manifest of a signed bundle, if it has one.
+    pub fn from_bundle(bundle: &Bundle) -> Result<Option<Self>> {
+        match bundle.code_signature_dir() {
+            Some(dir) if dir.join("CodeResources").is_file() => {
+                Ok(Some(plist::from_file(dir.join("CodeResources"))?))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Compares the manifest against the current resources of a bundle, sealed with
+    /// the rules of the manifest, and returns the differences sorted by path.
+    ///
+    /// The current manifest is compared when it has entries, the legacy manifest
+    /// otherwise.
+    pub fn verify(&self, bundle: &Bundle) -> Result<Vec<ResourceChange>> {
+        let current = self.seal(bundle)?;
+        let (expected, actual) = match self.files2.is_empty() {
+            false => (self.files2.clone(), current.files2),
+            true => (
+                legacy_resources(&self.files),
+                legacy_resources(&current.files),
+            ),
+        };
+        let mut changes = Vec::new();
+        let mut paths: Vec<_> = expected.keys().chain(actual.keys()).collect();
+        paths.sort();
+        paths.dedup();
+        for path in paths {
+            let change = match (expected.get(path), actual.get(path)) {
+                (Some(expected), Some(actual)) if !expected.matches(actual) => {
+                    ResourceChange::Modified(path.clone())
+                }
+                (Some(expected), None) if expected.optional != Some(true) => {
+                    ResourceChange::Removed(path.clone())
+                }
+                (None, Some(_)) => ResourceChange::Added(path.clone()),
+                _ => continue,
+            };
+            changes.push(change);
+        }
+        Ok(changes)
+    }
+}
+
+struct Sealer<'a> {
+    rules: Rules,
+    rules2: Rules,
+    excluded: Vec<String>,
+    manifest: &'a mut CodeResources,
+}
+
+impl Sealer<'_> {
+    fn seal_dir(&mut self, dir: &Path, prefix: &str) -> Result<()> {
+        let mut entries = std::fs::read_dir(dir)?
+            .map(|entry| Ok(entry?.path()))
+            .collect::<Result<Vec<_>>>()?;
+        entries.sort();
+        for path in entries {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            let relative = match prefix {
+                "" => name.into_owned(),
+                prefix => format!("{}/{}", prefix, name),
+            };
+            if self.excluded.contains(&relative) {
+                continue;
+            }
+            let metadata = std::fs::symlink_metadata(&path)?;
+            let seal2 = self.rules2.seal(&relative);
+            if metadata.file_type().is_symlink() {
+                if let Seal::Include { optional, .. } = seal2 {
+                    let target = std::fs::read_link(&path)?;
+                    self.manifest.files2.insert(
+                        relative,
+                        Resource {
+                            symlink: Some(relative_path(&target)),
+                            optional: optional.then_some(true),
+                            ..Default::default()
+                        },
+                    );
+                }
+            } else if metadata.is_dir() {
+                let nested = matches!(seal2, Seal::Include { nested: true, .. });
+                if nested && BundleKind::from_path(&path).is_some() {
+                    let bundle = Bundle::open(&path)?;
+                    let cdhash = match bundle.executable_path() {
+                        Some(executable) if executable.is_file() => cdhash(&executable)?,
+                        _ => None,
+                    };
+                    self.manifest.files2.insert(
+                        relative,
+                        Resource {
+                            cdhash: cdhash.map(Data::new),
+                            ..Default::default()
+                        },
+                    );
+                } else {
+                    self.seal_dir(&path, &relative)?;
+                }
+            } else {
+                self.seal_file(&path, relative, seal2)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn seal_file(&mut self, path: &Path, relative: String, seal2: Seal) -> Result<()> {
+        let data = std::fs::read(path)?;
+        let sha1 = Sha1::digest(&data).to_vec();
+        if let Seal::Include { optional, .. } = self.rules.seal(&relative) {
+            let hash = Data::new(sha1.clone());
+            let entry = match optional {
+                true => FileHash::Optional {
+                    hash,
+                    optional: true,
+                },
+                false => FileHash::Hash(hash),
+            };
+            self.manifest.files.insert(relative.clone(), entry);
+        }
+        let (optional, nested) = match seal2 {
+            Seal::Include { optional, nested } => (optional.then_some(true), nested),
+            Seal::Exclude => return Ok(()),
+        };
+        let cdhash = match nested {
+            true => code_directory_hash(&data)?,
+            false => None,
+        };
+        let resource = match cdhash {
+            Some(cdhash) => Resource {
+                cdhash: Some(Data::new(cdhash)),
+                ..Default::default()
+            },
+            None => Resource {
+                hash: Some(Data::new(sha1)),
+                hash2: Some(Data::new(Sha256::digest(&data).to_vec())),
+                optional,
+                ..Default::default()
+            },
+        };
+        self.manifest.files2.insert(relative, resource);
+        Ok(())
+    }
+}
+
+fn legacy_resources(files: &BTreeMap<String, FileHash>) -> BTreeMap<String, Resource> {
+    files
+        .iter()
+        .map(|(path, hash)| {
+            let resource = Resource {
+                hash: Some(hash.hash().clone()),
+                optional: hash.is_optional().then_some(true),
+                ..Default::default()
+            };
+            (path.clone(), resource)
+        })
+        .collect()
+}
+
+fn cdhash(path: &Path) -> Result<Option<Vec<u8>>> {
+    code_directory_hash(&std::fs::read(path)?)
+}
+
+/// Returns the `cdhash` of a signed Mach-O file: the hash of its code directory with
+/// the hash type of the code directory, truncated to 20 bytes.
+fn code_directory_hash(data: &[u8]) -> Result<Option<Vec<u8>>> {
+    let slices = match MachO::parse(data) {
+        Ok(slices) => slices,
+        Err(_) => return Ok(None),
+    };
+    let code_directory = match slices.first() {
+        Some(slice) => slice.code_directory()?,
+        None => None,
+    };
+    Ok(code_directory.map(|code_directory| {
+        const CS_HASHTYPE_SHA1: u8 = 1;
+        let mut hash = match code_directory.get(37) {
+            Some(&CS_HASHTYPE_SHA1) => Sha1::digest(code_directory).to_vec(),
+            _ => Sha256::digest(code_directory).to_vec(),
+        };
+        hash.truncate(20);
+        hash
+    }))
+}
+
+fn relative_path(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bundle::writer::BundleWriter, platform::Platform, prelude::*};
+
+    #[test]
+    fn generates_and_verifies_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let executable = dir.path().join("Example");
+        std::fs::write(&executable, b"binary").unwrap();
+        let resources = dir.path().join("en.lproj");
+        std::fs::create_dir_all(&resources).unwrap();
+        std::fs::write(resources.join("Localizable.strings"), b"").unwrap();
+        let mut writer = BundleWriter::new(InfoPlist::default(), &executable, Platform::Ios);
+        writer.resources.push(resources);
+        let bundle = writer.write(dir.path().join("Example.app")).unwrap();
+        std::fs::write(bundle.path.join("image.png"), b"png").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink("image.png", bundle.path.join("link.png")).unwrap();
+
+        let manifest = CodeResources::generate(&bundle).unwrap();
+        let empty_sha1 = Data::new(Sha1::digest(b"").to_vec());
+        assert_eq!(
+            manifest.files.get("en.lproj/Localizable.strings"),
+            Some(&FileHash::Optional {
+                hash: empty_sha1.clone(),
+                optional: true
+            })
+        );
+        assert_eq!(
+            manifest.files2["en.lproj/Localizable.strings"],
+            Resource {
+                hash: Some(empty_sha1),
+                hash2: Some(Data::new(Sha256::digest(b"").to_vec())),
+                optional: Some(true),
+                ..Default::default()
+            }
+        );
+        assert!(!manifest.files2.contains_key("Example"));
+        assert!(!manifest.files2.contains_key("Info.plist"));
+        assert!(!manifest.files2.contains_key("PkgInfo"));
+        assert!(manifest.files.contains_key("Info.plist"));
+        #[cfg(unix)]
+        assert_eq!(
+            manifest.files2["link.png"].symlink.as_deref(),
+            Some("image.png")
+        );
+
+        let mut xml = Vec::new();
+        plist::to_writer_xml(&mut xml, &manifest).unwrap();
+        let parsed: CodeResources = plist::from_bytes(&xml).unwrap();
+        assert_eq!(parsed, manifest);
+        assert_eq!(manifest.verify(&bundle).unwrap(), []);
+
+        std::fs::write(bundle.path.join("image.png"), b"changed").unwrap();
+        std::fs::write(bundle.path.join("new.txt"), b"new").unwrap();
+        std::fs::remove_file(bundle.path.join("en.lproj/Localizable.strings")).unwrap();
+        assert_eq!(
+            manifest.verify(&bundle).unwrap(),
+            [
+                ResourceChange::Modified("image.png".to_owned()),
+                ResourceChange::Added("new.txt".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn applies_highest_weight_rule() {
+        let manifest = CodeResources::with_default_rules(BundleLayout::Deep);
+        let rules = Rules::new(&manifest.rules2).unwrap();
+        let include = |optional, nested| Seal::Include { optional, nested };
+        assert_eq!(rules.seal("Info.plist"), Seal::Exclude);
+        assert_eq!(rules.seal("Resources/.DS_Store"), Seal::Exclude);
+        assert_eq!(
+            rules.seal("Resources/en.lproj/a.strings"),
+            include(true, false)
+        );
+        assert_eq!(rules.seal("Frameworks/Kit.framework"), include(false, true));
+        assert_eq!(rules.seal("Resources/image.png"), include(false, false));
+        assert!(Rules::new(&BTreeMap::from([(
+            "(".to_owned(),
+            ResourceRule::Include(true)
+        )]))
+        .is_err());
+    }
+}
@@ -0,0 +1,142 @@
+//! # Effective Entitlements.
+//!
+//! The entitlements code signing embeds in an executable.
+//!
+//! Xcode doesn't embed the `.entitlements` file of a target as is. It expands the
+//! `$(AppIdentifierPrefix)`, `$(TeamIdentifierPrefix)` and `$(CFBundleIdentifier)`
+//! variables in every value and adds the application identifier, the team identifier
+//! and whether the debugger may attach, which depends on the build configuration.
+
+use crate::{
+    entitlements::Entitlements, error::Result, platform::Platform,
+    xcode::build_settings::BuildSettings,
+};
+
+/// The build configuration an executable is signed for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum BuildConfiguration {
+    /// Development build that the debugger may attach to.
+    #[default]
+    Debug,
+    /// Distribution build.
+    Release,
+}
+
+/// The signing information used to compute the effective entitlements.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SigningSettings {
+    /// The identifier of the team that signs the executable.
+    pub team_id: String,
+    /// The bundle identifier of the signed bundle.
+    pub bundle_id: String,
+    /// The build configuration.
+    pub configuration: BuildConfiguration,
+    /// The platform of the executable, which selects the names of the injected
+    /// entitlements.
+    pub platform: Platform,
+}
+
+impl SigningSettings {
+    /// Returns the application identifier, `<team id>.<bundle id>`.
+    pub fn application_identifier(&self) -> String {
+        format!("{}.{}", self.team_id, self.bundle_id)
+    }
+
+    /// Returns the final entitlements codesign embeds for the given entitlements file.
+    ///
+    /// Variables are expanded in every string and string array, and the application
+    /// identifier, team identifier and `get-task-allow` entitlements are set. On macOS,
+    /// `com.apple.security.get-task-allow` is only added to debug builds, like Xcode
+    /// does.
+    pub fn effective_entitlements(&self, entitlements: &Entitlements) -> Result<Entitlements> {
+        let mut effective = self.variables().expand(entitlements)?.value;
+        let code_signing = &mut effective.code_signing;
+        let debug = self.configuration == BuildConfiguration::Debug;
+        code_signing.team_identifier = Some(self.team_id.clone());
+        if self.platform == Platform::MacOs {
+            code_signing.macos_application_identifier = Some(self.application_identifier());
+            if debug {
+                code_signing.security_get_task_allow = Some(true);
+            }
+        } else {
+            code_signing.application_identifier = Some(self.application_identifier());
+            code_signing.get_task_allow = Some(debug);
+        }
+        Ok(effective)
+    }
+
+    fn variables(&self) -> BuildSettings {
+        let prefix = format!("{}.", self.team_id);
+        BuildSettings::from_iter([
+            ("AppIdentifierPrefix", prefix.clone()),
+            ("TeamIdentifierPrefix", prefix),
+            ("CFBundleIdentifier", self.bundle_id.clone()),
+            ("PRODUCT_BUNDLE_IDENTIFIER", self.bundle_id.clone()),
+            ("DEVELOPMENT_TEAM", self.team_id.clone()),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entitlements::prelude::*;
+
+    #[test]
+    fn expands_variables_and_injects_signing_entitlements() {
+        let entitlements = Entitlements {
+            security: Security {
+                keychain_access_groups: Some(vec![
+                    "$(AppIdentifierPrefix)$(CFBundleIdentifier)".to_owned(),
+                    "${TeamIdentifierPrefix}shared".to_owned(),
+                ]),
+                ..Default::default()
+            },
+            icloud: ICloud {
+                icloud_key_value_store: Some("$(TeamIdentifierPrefix)$(UNKNOWN)".to_owned()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut settings = SigningSettings {
+            team_id: "ABCDE12345".to_owned(),
+            bundle_id: "com.example.app".to_owned(),
+            configuration: BuildConfiguration::Release,
+            platform: Platform::Ios,
+        };
+        let effective = settings.effective_entitlements(&entitlements).unwrap();
+        assert_eq!(
+            effective.security.keychain_access_groups,
+            Some(vec![
+                "ABCDE12345.com.example.app".to_owned(),
+                "ABCDE12345.shared".to_owned(),
+            ])
+        );
+        assert_eq!(
+            effective.icloud.icloud_key_value_store.as_deref(),
+            Some("ABCDE12345.$(UNKNOWN)")
+        );
+        assert_eq!(
+            effective.code_signing,
+            CodeSigning {
+                application_identifier: Some("ABCDE12345.com.example.app".to_owned()),
+                team_identifier: Some("ABCDE12345".to_owned()),
+                get_task_allow: Some(false),
+                ..Default::default()
+            }
+        );
+
+        settings.platform = Platform::MacOs;
+        settings.configuration = BuildConfiguration::Debug;
+        let effective = settings.effective_entitlements(&entitlements).unwrap();
+        assert_eq!(
+            effective.code_signing,
+            CodeSigning {
+                macos_application_identifier: Some("ABCDE12345.com.example.app".to_owned()),
+                team_identifier: Some("ABCDE12345".to_owned()),
+                security_get_task_allow: Some(true),
+                ..Default::default()
+            }
+        );
+    }
+}
@@ -0,0 +1,21 @@
+//! # Code Signing.
+//!
+//! The resources that code signing derives from a bundle and embeds in its signature.
+//!
+//! When signing an app, the signing tool combines the entitlements file of the target
+//! with information from the developer account, embeds the result in the code
+//! signature of the executable, and seals the resources of the bundle.
+//!
+//! Official documentation: <https://developer.apple.com/documentation/security/code_signing_services>
+
+#[cfg(feature = "code_resources")]
+pub mod code_resources;
+pub mod der_entitlements;
+pub mod effective_entitlements;
+
+pub mod prelude {
+    #[cfg(feature = "code_resources")]
+    pub use super::code_resources::*;
+    pub use super::der_entitlements::*;
+    pub use super::effective_entitlements::*;
+}
@@ -0,0 +1,175 @@
+//! Minimal reader for the BER and DER encodings of ASN.1, and writer for DER.
+//!
+//! Only what code signing structures need is supported: single-byte tags, definite
+//! and indefinite lengths, and constructed octet strings.
+
+use crate::error::{Error, Result};
+use std::borrow::Cow;
+
+pub(crate) const BOOLEAN: u8 = 0x01;
+pub(crate) const INTEGER: u8 = 0x02;
+pub(crate) const OCTET_STRING: u8 = 0x04;
+pub(crate) const OBJECT_IDENTIFIER: u8 = 0x06;
+pub(crate) const UTF8_STRING: u8 = 0x0c;
+pub(crate) const SEQUENCE: u8 = 0x30;
+pub(crate) const SET: u8 = 0x31;
+pub(crate) const CONTEXT_0: u8 = 0xa0;
+pub(crate) const CONTEXT_16: u8 = 0xb0;
+pub(crate) const APPLICATION_16: u8 = 0x70;
+
+const CONSTRUCTED: u8 = 0x20;
+
+/// The deepest nesting of elements read recursively, so that malformed input can't
+/// overflow the stack.
+pub(crate) const MAX_DEPTH: usize = 32;
+
+/// A single tag-length-value element.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Element<'a> {
+    pub tag: u8,
+    pub contents: &'a [u8],
+}
+
+impl<'a> Element<'a> {
+    /// Returns a reader over the elements nested in a constructed element.
+    pub fn children(&self) -> Reader<'a> {
+        Reader::new(self.contents)
+    }
+
+    /// Returns the bytes of an octet string, joining the segments of the constructed
+    /// form.
+    pub fn octets(&self) -> Result<Cow<'a, [u8]>> {
+        self.octets_at(0)
+    }
+
+    fn octets_at(&self, depth: usize) -> Result<Cow<'a, [u8]>> {
+        if self.tag == OCTET_STRING {
+            return Ok(Cow::Borrowed(self.contents));
+        }
+        if self.tag != OCTET_STRING | CONSTRUCTED {
+            return Err(unexpected(OCTET_STRING, self.tag));
+        }
+        if depth == MAX_DEPTH {
+            return Err(too_deep());
+        }
+        let mut octets = Vec::new();
+        let mut children = self.children();
+        while !children.is_empty() {
+            octets.extend_from_slice(&children.read()?.octets_at(depth + 1)?);
+        }
+        Ok(Cow::Owned(octets))
+    }
+}
+
+/// Reads consecutive elements from a buffer.
+#[derive(Debug, Clone)]
+pub(crate) struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Reads the next element.
+    pub fn read(&mut self) -> Result<Element<'a>> {
+        let (element, len) = parse(self.data)?;
+        self.data = &self.data[len..];
+        Ok(element)
+    }
+
+    /// Reads the next element, failing if it doesn't have the given tag.
+    pub fn expect(&mut self, tag: u8) -> Result<Element<'a>> {
+        let element = self.read()?;
+        match element.tag == tag {
+            true => Ok(element),
+            false => Err(unexpected(tag, element.tag)),
+        }
+    }
+}
+
+/// Appends the DER encoding of an element to `out`.
+pub(crate) fn write(tag: u8, contents: &[u8], out: &mut Vec<u8>) {
+    out.push(tag);
+    match contents.len() {
+        len if len < 0x80 => out.push(len as u8),
+        len => {
+            let bytes = len.to_be_bytes();
+            let skip = bytes.iter().take_while(|&&byte| byte == 0).count();
+            out.push(0x80 | (bytes.len() - skip) as u8);
+            out.extend_from_slice(&bytes[skip..]);
+        }
+    }
+    out.extend_from_slice(contents);
+}
+
+/// Parses the element at the start of `data`, returning it with its encoded length.
+fn parse(data: &[u8]) -> Result<(Element<'_>, usize)> {
+    parse_at(data, 0)
+}
+
+/// Parses an element nested in `depth` elements of indefinite length.
+fn parse_at(data: &[u8], depth: usize) -> Result<(Element<'_>, usize)> {
+    let truncated = || Error::InvalidDer("truncated element".to_owned());
+    let (&tag, rest) = data.split_first().ok_or_else(truncated)?;
+    if tag & 0x1f == 0x1f {
+        return Err(Error::InvalidDer(format!(
+            "unsupported multi-byte tag {:#04x}",
+            tag
+        )));
+    }
+    let (&first, rest) = rest.split_first().ok_or_else(truncated)?;
+    if first == 0x80 {
+        if tag & CONSTRUCTED == 0 {
+            return Err(Error::InvalidDer(
+                "indefinite length of a primitive element".to_owned(),
+            ));
+        }
+        if depth == MAX_DEPTH {
+            return Err(too_deep());
+        }
+        let mut offset = 0;
+        while !rest[offset..].starts_with(&[0, 0]) {
+            if offset >= rest.len() {
+                return Err(truncated());
+            }
+            offset += parse_at(&rest[offset..], depth + 1)?.1;
+        }
+        let contents = &rest[..offset];
+        return Ok((Element { tag, contents }, 2 + offset + 2));
+    }
+    let (len, header) = match first {
+        len if len < 0x80 => (len as usize, 2),
+        size => {
+            let size = (size & 0x7f) as usize;
+            if size > std::mem::size_of::<usize>() || rest.len() < size {
+                return Err(truncated());
+            }
+            let len = rest[..size]
+                .iter()
+                .fold(0usize, |len, &byte| len << 8 | byte as usize);
+            (len, 2 + size)
+        }
+    };
+    let contents = header
+        .checked_add(len)
+        .and_then(|end| data.get(header..end))
+        .ok_or_else(truncated)?;
+    Ok((Element { tag, contents }, header + len))
+}
+
+pub(crate) fn too_deep() -> Error {
+    Error::InvalidDer("elements nested too deeply".to_owned())
+}
+
+fn unexpected(expected: u8, found: u8) -> Error {
+    Error::InvalidDer(format!(
+        "expected tag {:#04x}, found {:#04x}",
+        expected, found
+    ))
+}
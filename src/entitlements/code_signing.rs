@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+
+/// Code Signing
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq, Eq)]
+pub struct CodeSigning {
+    /// The application identifier of an iOS, tvOS, watchOS or visionOS app, in the
+    /// `<team identifier>.<bundle identifier>` format.
+    ///
+    /// Xcode adds this entitlement when code signing the app. The value must be
+    /// authorized by the provisioning profile.
+    ///
+    /// ## Availability
+    /// * iOS 2.0+
+    /// * tvOS 9.0+
+    /// * watchOS 2.0+
+    ///
+    /// ## Framework
+    /// * Security
+    #[serde(
+        rename = "application-identifier",
+        serialize_with = "crate::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub application_identifier: Option<String>,
+    /// The application identifier of a macOS app, in the
+    /// `<team identifier>.<bundle identifier>` format.
+    ///
+    /// Xcode adds this entitlement when code signing the app. The value must be
+    /// authorized by the provisioning profile.
+    ///
+    /// ## Availability
+    /// * macOS 10.7+
+    ///
+    /// ## Framework
+    /// * Security
+    #[serde(
+        rename = "com.apple.application-identifier",
+        serialize_with = "crate::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub macos_application_identifier: Option<String>,
+    /// The identifier of the team that signed the app.
+    ///
+    /// Xcode adds this entitlement when code signing the app.
+    ///
+    /// ## Availability
+    /// * iOS 2.0+
+    /// * macOS 10.7+
+    /// * tvOS 9.0+
+    /// * watchOS 2.0+
+    ///
+    /// ## Framework
+    /// * Security
+    #[serde(
+        rename = "com.apple.developer.team-identifier",
+        serialize_with = "crate::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub team_identifier: Option<String>,
+    /// A Boolean value that indicates whether other processes, like the debugger, can
+    /// attach to the app.
+    ///
+    /// Xcode sets this entitlement to `true` for development builds and to `false` for
+    /// distribution builds.
+    ///
+    /// ## Availability
+    /// * iOS 2.0+
+    /// * tvOS 9.0+
+    /// * watchOS 2.0+
+    ///
+    /// ## Framework
+    /// * Security
+    #[serde(
+        rename = "get-task-allow",
+        serialize_with = "crate::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub get_task_allow: Option<bool>,
+    /// A Boolean value that indicates whether other processes, like the debugger, can
+    /// attach to a macOS app.
+    ///
+    /// Xcode adds this entitlement to development builds of apps that use the Hardened
+    /// Runtime.
+    ///
+    /// ## Availability
+    /// * macOS 10.14+
+    ///
+    /// ## Framework
+    /// * Security
+    #[serde(
+        rename = "com.apple.security.get-task-allow",
+        serialize_with = "crate::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub security_get_task_allow: Option<bool>,
+}
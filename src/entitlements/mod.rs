@@ -29,6 +29,7 @@
 pub mod app_clips;
 pub mod authentication;
 pub mod car_play;
+pub mod code_signing;
 pub mod contacts;
 pub mod deprecated_entitlements;
 pub mod education;
@@ -52,6 +53,7 @@ pub mod prelude {
     pub use super::app_clips::*;
     pub use super::authentication::*;
     pub use super::car_play::*;
+    pub use super::code_signing::*;
     pub use super::contacts::*;
     pub use super::deprecated_entitlements::*;
     pub use super::education::*;
@@ -89,6 +91,9 @@ pub struct Entitlements {
     /// Car Play
     #[serde(flatten)]
     pub car_play: CarPlay,
+    /// Code Signing
+    #[serde(flatten)]
+    pub code_signing: CodeSigning,
     /// Contacts
     #[serde(flatten)]
     pub contacts: Contacts,
@@ -1,4 +1,6 @@
-use serde::{Deserialize, Serialize};
+use crate::error::{Error, Result};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, str::FromStr};
 
 /// Networking
 #[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq, Eq)]
@@ -125,7 +127,7 @@ pub struct Networking {
         serialize_with = "crate::serialize_option",
         skip_serializing_if = "Option::is_none"
     )]
-    pub associated_domains: Option<Vec<String>>,
+    pub associated_domains: Option<Vec<AssociatedDomainEntry>>,
     /// A Boolean value that indicates whether an app can send or receive IP multicast
     /// traffic.
     ///
@@ -209,3 +211,214 @@ pub enum PersonalVPN {
     #[serde(rename = "allow-vpn")]
     AllowVpn,
 }
+
+/// An entry of the associated domains entitlement.
+///
+/// Parsed from and serialized to the
+/// `<service>:<fully qualified domain>[?mode=<alternate mode>]` format.
+///
+/// ```
+/// use apple_bundle::prelude::*;
+///
+/// let domain: AssociatedDomain = "applinks:example.com?mode=developer".parse().unwrap();
+/// assert_eq!(domain.service, AssociatedDomainService::AppLinks);
+/// assert_eq!(domain.domain, "example.com");
+/// assert_eq!(domain.mode, Some(AssociatedDomainMode::Developer));
+/// assert_eq!(domain.to_string(), "applinks:example.com?mode=developer");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct AssociatedDomain {
+    /// The service the domain is associated with.
+    pub service: AssociatedDomainService,
+    /// The fully qualified domain. May start with `*.` to match all subdomains.
+    pub domain: String,
+    /// The alternate mode used to bypass the Apple-managed CDN.
+    pub mode: Option<AssociatedDomainMode>,
+}
+
+impl AssociatedDomain {
+    /// Creates an associated domain without an alternate mode.
+    pub fn new(service: AssociatedDomainService, domain: impl Into<String>) -> Self {
+        Self {
+            service,
+            domain: domain.into(),
+            mode: None,
+        }
+    }
+
+    /// Returns `true` if the domain starts with the `*.` wildcard.
+    pub fn is_wildcard(&self) -> bool {
+        self.domain.starts_with("*.")
+    }
+
+    /// Returns the host serving the `apple-app-site-association` file of this domain.
+    ///
+    /// For a wildcard domain such as `*.example.com`, the file is served by
+    /// `example.com`.
+    pub fn file_host(&self) -> &str {
+        self.domain.strip_prefix("*.").unwrap_or(&self.domain)
+    }
+
+    /// Returns `true` if `host` is covered by this domain.
+    ///
+    /// A wildcard domain such as `*.example.com` matches all subdomains of
+    /// `example.com`, but not `example.com` itself. Comparison is case-insensitive.
+    pub fn matches_host(&self, host: &str) -> bool {
+        let host = host.trim_end_matches('.').to_ascii_lowercase();
+        let domain = self.domain.to_ascii_lowercase();
+        match domain.strip_prefix('*') {
+            Some(suffix) => host.len() > suffix.len() && host.ends_with(suffix),
+            None => host == domain,
+        }
+    }
+}
+
+impl fmt::Display for AssociatedDomain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.service, self.domain)?;
+        if let Some(mode) = &self.mode {
+            write!(f, "?mode={}", mode)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for AssociatedDomain {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::InvalidAssociatedDomain(s.to_owned());
+        let (service, rest) = s.split_once(':').ok_or_else(invalid)?;
+        let service = service.parse().map_err(|_| invalid())?;
+        let (domain, mode) = match rest.split_once('?') {
+            Some((domain, query)) => {
+                let mode = query.strip_prefix("mode=").ok_or_else(invalid)?;
+                (domain, Some(mode.parse().map_err(|_| invalid())?))
+            }
+            None => (rest, None),
+        };
+        if domain.is_empty()
+            || domain
+                .chars()
+                .any(|c| c.is_whitespace() || matches!(c, '/' | ':' | '?' | '#'))
+        {
+            return Err(invalid());
+        }
+        Ok(Self {
+            service,
+            domain: domain.to_owned(),
+            mode,
+        })
+    }
+}
+
+impl Serialize for AssociatedDomain {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for AssociatedDomain {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(de::Error::custom)
+    }
+}
+
+/// An entry of the associated domains entitlement.
+///
+/// Entries that aren't valid associated domains are kept as written, so that a single
+/// malformed entry doesn't prevent reading the rest of the entitlements.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+#[serde(untagged)]
+pub enum AssociatedDomainEntry {
+    /// A valid associated domain.
+    Domain(AssociatedDomain),
+    /// An entry that isn't a valid associated domain.
+    Invalid(String),
+}
+
+impl AssociatedDomainEntry {
+    /// Returns the associated domain, unless the entry is invalid.
+    pub fn domain(&self) -> Option<&AssociatedDomain> {
+        match self {
+            Self::Domain(domain) => Some(domain),
+            Self::Invalid(_) => None,
+        }
+    }
+}
+
+impl From<AssociatedDomain> for AssociatedDomainEntry {
+    fn from(domain: AssociatedDomain) -> Self {
+        Self::Domain(domain)
+    }
+}
+
+impl FromStr for AssociatedDomainEntry {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        s.parse().map(Self::Domain)
+    }
+}
+
+/// Associated Domain Service
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum AssociatedDomainService {
+    /// Use this service for shared web credentials.
+    #[serde(rename = "webcredentials")]
+    WebCredentials,
+    /// Use this service for universal links.
+    #[serde(rename = "applinks")]
+    AppLinks,
+    /// Use this service for Handoff.
+    #[serde(rename = "activitycontinuation")]
+    ActivityContinuation,
+    /// Use this service for an App Clip.
+    #[serde(rename = "appclips")]
+    AppClips,
+}
+
+impl fmt::Display for AssociatedDomainService {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&serde_plain::to_string(self).unwrap())
+    }
+}
+
+impl FromStr for AssociatedDomainService {
+    type Err = serde_plain::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_plain::from_str(s)
+    }
+}
+
+/// Associated Domain Alternate Mode
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AssociatedDomainMode {
+    /// Only devices in developer mode can access the domain.
+    #[serde(rename = "developer")]
+    Developer,
+    /// Only devices managed with a mobile device management (MDM) profile can access
+    /// the domain.
+    #[serde(rename = "managed")]
+    Managed,
+    /// Only devices that are in both developer and managed modes at the same time can
+    /// access the domain.
+    #[serde(rename = "developer+managed")]
+    DeveloperManaged,
+}
+
+impl fmt::Display for AssociatedDomainMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&serde_plain::to_string(self).unwrap())
+    }
+}
+
+impl FromStr for AssociatedDomainMode {
+    type Err = serde_plain::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_plain::from_str(s)
+    }
+}
@@ -0,0 +1,148 @@
+use std::fmt;
+
+/// Result type used across this crate.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Errors returned by the parsers, generators and checks of this crate.
+#[derive(Debug)]
+pub enum Error {
+    /// An associated domain entry doesn't follow the
+    /// `<service>:<fully qualified domain>[?mode=<alternate mode>]` format.
+    InvalidAssociatedDomain(String),
+    /// A URL that can't be matched against universal links.
+    InvalidUrl(String),
+    /// Malformed or unsupported ASN.1 data.
+    InvalidDer(String),
+    /// Malformed or unsupported Mach-O file.
+    InvalidMachO(String),
+    /// A directory that isn't a well-formed bundle.
+    InvalidBundle(String),
+    /// A code resources rule that isn't a valid regular expression.
+    InvalidResourceRule(String),
+    /// An `.xcconfig` file that can't be parsed.
+    InvalidXcConfig(String),
+    /// An old-style ASCII property list that can't be parsed.
+    InvalidAsciiPlist(String),
+    /// An Xcode project that can't be read.
+    InvalidProject(String),
+    /// An XML property list document that can't be parsed.
+    InvalidXmlPlist(String),
+    /// A key path that doesn't match the structure of a property list document.
+    InvalidKeyPath(String),
+    /// A Rust target triple that isn't an Apple target.
+    InvalidTarget(String),
+    /// A `Cargo.toml` without the expected package metadata.
+    InvalidManifest(String),
+    /// Failed to read or write a file.
+    Io(std::io::Error),
+    /// Failed to read or write a property list.
+    #[cfg(feature = "plist")]
+    Plist(plist::Error),
+    /// Failed to read or write JSON.
+    #[cfg(feature = "serde_json")]
+    Json(serde_json::Error),
+    /// Failed to read or write a zip archive.
+    #[cfg(feature = "zip")]
+    Zip(zip::result::ZipError),
+    /// Failed to read TOML.
+    #[cfg(feature = "toml")]
+    TomlDe(toml::de::Error),
+    /// Failed to write TOML.
+    #[cfg(feature = "toml")]
+    TomlSer(toml::ser::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidAssociatedDomain(value) => {
+                write!(f, "invalid associated domain `{}`", value)
+            }
+            Error::InvalidUrl(value) => write!(f, "invalid url `{}`", value),
+            Error::InvalidDer(reason) => write!(f, "invalid der: {}", reason),
+            Error::InvalidMachO(reason) => write!(f, "invalid mach-o: {}", reason),
+            Error::InvalidBundle(reason) => write!(f, "invalid bundle: {}", reason),
+            Error::InvalidResourceRule(reason) => write!(f, "invalid resource rule {}", reason),
+            Error::InvalidXcConfig(reason) => write!(f, "invalid xcconfig: {}", reason),
+            Error::InvalidAsciiPlist(reason) => {
+                write!(f, "invalid ASCII property list: {}", reason)
+            }
+            Error::InvalidProject(reason) => write!(f, "invalid Xcode project: {}", reason),
+            Error::InvalidXmlPlist(reason) => write!(f, "invalid XML property list: {}", reason),
+            Error::InvalidKeyPath(reason) => write!(f, "invalid key path: {}", reason),
+            Error::InvalidTarget(value) => write!(f, "invalid apple target `{}`", value),
+            Error::InvalidManifest(reason) => write!(f, "invalid manifest: {}", reason),
+            Error::Io(err) => write!(f, "io error: {}", err),
+            #[cfg(feature = "plist")]
+            Error::Plist(err) => write!(f, "plist error: {}", err),
+            #[cfg(feature = "serde_json")]
+            Error::Json(err) => write!(f, "json error: {}", err),
+            #[cfg(feature = "zip")]
+            Error::Zip(err) => write!(f, "zip error: {}", err),
+            #[cfg(feature = "toml")]
+            Error::TomlDe(err) => write!(f, "toml error: {}", err),
+            #[cfg(feature = "toml")]
+            Error::TomlSer(err) => write!(f, "toml error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            #[cfg(feature = "plist")]
+            Error::Plist(err) => Some(err),
+            #[cfg(feature = "serde_json")]
+            Error::Json(err) => Some(err),
+            #[cfg(feature = "zip")]
+            Error::Zip(err) => Some(err),
+            #[cfg(feature = "toml")]
+            Error::TomlDe(err) => Some(err),
+            #[cfg(feature = "toml")]
+            Error::TomlSer(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+#[cfg(feature = "plist")]
+impl From<plist::Error> for Error {
+    fn from(err: plist::Error) -> Self {
+        Error::Plist(err)
+    }
+}
+
+#[cfg(feature = "serde_json")]
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Json(err)
+    }
+}
+
+#[cfg(feature = "zip")]
+impl From<zip::result::ZipError> for Error {
+    fn from(err: zip::result::ZipError) -> Self {
+        Error::Zip(err)
+    }
+}
+
+#[cfg(feature = "toml")]
+impl From<toml::de::Error> for Error {
+    fn from(err: toml::de::Error) -> Self {
+        Error::TomlDe(err)
+    }
+}
+
+#[cfg(feature = "toml")]
+impl From<toml::ser::Error> for Error {
+    fn from(err: toml::ser::Error) -> Self {
+        Error::TomlSer(err)
+    }
+}
@@ -434,7 +434,7 @@ pub struct BackgroundExecution {
     /// ## Framework
     /// * UIKit
     #[serde(
-        rename(serialize = "UIDeviceFamily"),
+        rename = "UIDeviceFamily",
         serialize_with = "crate::serialize_option",
         skip_serializing_if = "Option::is_none"
     )]
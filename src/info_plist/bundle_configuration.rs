@@ -11,7 +11,8 @@
 //! ## Framework
 //! * Bundle Resources
 
-use serde::{Deserialize, Serialize};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{convert::Infallible, fmt, str::FromStr};
 
 /// Categorization
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Default)]
@@ -36,7 +37,26 @@ pub struct Categorization {
         serialize_with = "crate::serialize_option",
         skip_serializing_if = "Option::is_none"
     )]
-    pub bundle_package_type: Option<String>,
+    pub bundle_package_type: Option<BundlePackageType>,
+    /// The creator code of the bundle.
+    ///
+    /// This key consists of a four-letter code that, together with the package type,
+    /// forms the `PkgInfo` file of the bundle. Xcode sets it to `????`.
+    ///
+    /// ## Availability
+    /// * iOS 2.0+
+    /// * macOS 10.0+
+    /// * tvOS 9.0+
+    /// * watchOS 2.0+
+    ///
+    /// ## Framework
+    /// Core Foundation
+    #[serde(
+        rename = "CFBundleSignature",
+        serialize_with = "crate::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub bundle_signature: Option<String>,
     /// The category that best describes your app for the App Store.
     ///
     /// ## Availability
@@ -340,6 +360,41 @@ pub struct OperatingSystemVersion {
         skip_serializing_if = "Option::is_none"
     )]
     pub watch_kit_app: Option<bool>,
+    /// The platforms that the bundle was built for, like `iPhoneOS` or
+    /// `iPhoneSimulator`.
+    ///
+    /// Xcode inserts this key based on the SDK of the build.
+    ///
+    /// ## Availability
+    /// * iOS 2.0+
+    /// * macOS 10.0+
+    /// * tvOS 9.0+
+    /// * watchOS 2.0+
+    ///
+    /// ## Framework
+    /// * Core Foundation
+    #[serde(
+        rename = "CFBundleSupportedPlatforms",
+        serialize_with = "crate::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub supported_platforms: Option<Vec<String>>,
+    /// The name of the SDK platform that the bundle was built with, like `iphoneos`
+    /// or `macosx`.
+    ///
+    /// Xcode inserts this key based on the SDK of the build.
+    ///
+    /// ## Availability
+    /// * iOS 2.0+
+    /// * macOS 10.0+
+    /// * tvOS 9.0+
+    /// * watchOS 2.0+
+    #[serde(
+        rename = "DTPlatformName",
+        serialize_with = "crate::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub platform_name: Option<String>,
 }
 
 /// Localization
@@ -561,6 +616,94 @@ pub enum AppCategoryType {
     Weather,
 }
 
+/// Bundle Package Type
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum BundlePackageType {
+    /// An app, `APPL`.
+    App,
+    /// A framework, `FMWK`.
+    Framework,
+    /// A loadable bundle or a resource bundle, `BNDL`.
+    Bundle,
+    /// An app extension or an XPC service, `XPC!`.
+    XpcService,
+    /// A kernel extension, `KEXT`.
+    KernelExtension,
+    /// A system extension, `SYSX`.
+    SystemExtension,
+    /// A DriverKit driver extension, `DEXT`.
+    DriverExtension,
+    /// Any other four-character code.
+    Custom(String),
+}
+
+impl BundlePackageType {
+    /// Returns the four-character code.
+    pub fn code(&self) -> &str {
+        match self {
+            Self::App => "APPL",
+            Self::Framework => "FMWK",
+            Self::Bundle => "BNDL",
+            Self::XpcService => "XPC!",
+            Self::KernelExtension => "KEXT",
+            Self::SystemExtension => "SYSX",
+            Self::DriverExtension => "DEXT",
+            Self::Custom(code) => code,
+        }
+    }
+
+    /// Returns the package type that Xcode derives from a bundle extension, without
+    /// the leading dot.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        Some(match extension.to_ascii_lowercase().as_str() {
+            "app" => Self::App,
+            "framework" => Self::Framework,
+            "bundle" | "plugin" => Self::Bundle,
+            "appex" | "xpc" => Self::XpcService,
+            "kext" => Self::KernelExtension,
+            "systemextension" => Self::SystemExtension,
+            "dext" => Self::DriverExtension,
+            _ => return None,
+        })
+    }
+}
+
+impl fmt::Display for BundlePackageType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+impl FromStr for BundlePackageType {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "APPL" => Self::App,
+            "FMWK" => Self::Framework,
+            "BNDL" => Self::Bundle,
+            "XPC!" => Self::XpcService,
+            "KEXT" => Self::KernelExtension,
+            "SYSX" => Self::SystemExtension,
+            "DEXT" => Self::DriverExtension,
+            code => Self::Custom(code.to_owned()),
+        })
+    }
+}
+
+impl Serialize for BundlePackageType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.code())
+    }
+}
+
+impl<'de> Deserialize<'de> for BundlePackageType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(de::Error::custom)
+    }
+}
+
 /// Operating System Version
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
 pub struct MinimumSystemVersionByArchitecture {
@@ -304,6 +304,34 @@ pub struct Orientation {
         serialize_with = "crate::serialize_vec_enum_option"
     )]
     pub supported_interface_orientations: Option<Vec<InterfaceOrientation>>,
+    /// The orientations the app supports on iPad, overriding
+    /// `UISupportedInterfaceOrientations`.
+    ///
+    /// ## Availability
+    /// * iOS 3.2+
+    ///
+    /// ## Framework
+    /// * UIKit
+    #[serde(
+        rename = "UISupportedInterfaceOrientations~ipad",
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::serialize_vec_enum_option"
+    )]
+    pub supported_interface_orientations_ipad: Option<Vec<InterfaceOrientation>>,
+    /// The orientations the app supports on iPhone, overriding
+    /// `UISupportedInterfaceOrientations`.
+    ///
+    /// ## Availability
+    /// * iOS 3.2+
+    ///
+    /// ## Framework
+    /// * UIKit
+    #[serde(
+        rename = "UISupportedInterfaceOrientations~iphone",
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::serialize_vec_enum_option"
+    )]
+    pub supported_interface_orientations_iphone: Option<Vec<InterfaceOrientation>>,
 }
 
 /// Styling
@@ -0,0 +1,305 @@
+//! # IPA Archive.
+//!
+//! The archive format of iOS, tvOS, watchOS and visionOS apps.
+//!
+//! An IPA file is a zip archive holding the app bundle in the `Payload` directory. Apps
+//! built with Swift before ABI stability carry the Swift runtime libraries in
+//! `SwiftSupport`, and App Store submissions can include the symbol files of the app in
+//! `Symbols`.
+
+use crate::{
+    bundle::{directory::Bundle, directory::BundleKind, nested::NestedLocation},
+    error::{Error, Result},
+    info_plist::InfoPlist,
+    provisioning_profile::ProvisioningProfile,
+};
+use std::{
+    fs::File,
+    io::{Read, Seek, Write},
+    path::{Path, PathBuf},
+};
+use zip::{write::SimpleFileOptions, CompressionMethod, DateTime, ZipArchive, ZipWriter};
+
+/// The largest entry read into memory, whatever size the archive declares for it.
+const MAX_ENTRY_SIZE: u64 = 1 << 30;
+
+/// An IPA archive opened for reading.
+#[derive(Debug)]
+pub struct IpaReader<R> {
+    archive: ZipArchive<R>,
+    app_dir: String,
+}
+
+impl IpaReader<File> {
+    /// Opens the IPA file at `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::new(File::open(path)?)
+    }
+}
+
+impl<R: Read + Seek> IpaReader<R> {
+    /// Reads an IPA archive and locates the app in its `Payload` directory.
+    pub fn new(reader: R) -> Result<Self> {
+        let archive = ZipArchive::new(reader)?;
+        let app_dir = archive
+            .file_names()
+            .filter_map(|name| {
+                let name = name.strip_prefix("Payload/")?;
+                let (app, _) = name.split_once('/')?;
+                app.ends_with(".app").then(|| format!("Payload/{}/", app))
+            })
+            .min()
+            .ok_or_else(|| Error::InvalidBundle("no app in the IPA Payload".to_owned()))?;
+        Ok(Self { archive, app_dir })
+    }
+
+    /// Returns the path of the app directory in the archive, like
+    /// `Payload/Example.app/`.
+    pub fn app_dir(&self) -> &str {
+        &self.app_dir
+    }
+
+    /// Returns the paths of every entry in the archive, in archive order.
+    pub fn file_names(&self) -> Vec<&str> {
+        (0..self.archive.len())
+            .filter_map(|index| self.archive.name_for_index(index))
+            .collect()
+    }
+
+    /// Reads an entry of the app, given its path relative to the app directory.
+    ///
+    /// Fails for entries larger than 1 GiB.
+    pub fn read_app_file(&mut self, path: &str) -> Result<Option<Vec<u8>>> {
+        let name = format!("{}{}", self.app_dir, path);
+        let mut file = match self.archive.by_name(&name) {
+            Ok(file) => file,
+            Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let mut data = Vec::new();
+        file.by_ref()
+            .take(MAX_ENTRY_SIZE + 1)
+            .read_to_end(&mut data)?;
+        if data.len() as u64 > MAX_ENTRY_SIZE {
+            return Err(Error::InvalidBundle(format!(
+                "{} is larger than {} bytes",
+                name, MAX_ENTRY_SIZE
+            )));
+        }
+        Ok(Some(data))
+    }
+
+    /// Parses the information property list of the app.
+    pub fn info_plist(&mut self) -> Result<InfoPlist> {
+        match self.read_app_file("Info.plist")? {
+            Some(data) => Ok(plist::from_bytes(&data)?),
+            None => Err(Error::InvalidBundle(format!(
+                "no Info.plist in {}",
+                self.app_dir
+            ))),
+        }
+    }
+
+    /// Parses the provisioning profile embedded in the app, if it has one.
+    pub fn provisioning_profile(&mut self) -> Result<Option<ProvisioningProfile>> {
+        self.read_app_file("embedded.mobileprovision")?
+            .map(|data| ProvisioningProfile::from_bytes(&data))
+            .transpose()
+    }
+
+    /// Returns the paths of the bundles directly nested in the app, relative to the app
+    /// directory, like `PlugIns/Widget.appex`.
+    pub fn nested_bundle_paths(&self) -> Vec<String> {
+        let mut paths: Vec<String> = self
+            .archive
+            .file_names()
+            .filter_map(|name| {
+                let name = name.strip_prefix(self.app_dir.as_str())?;
+                NestedLocation::ALL.iter().find_map(|location| {
+                    let rest = name.strip_prefix(location.dir())?.strip_prefix('/')?;
+                    let (bundle, _) = rest.split_once('/')?;
+                    BundleKind::from_path(bundle)?;
+                    Some(format!("{}/{}", location.dir(), bundle))
+                })
+            })
+            .collect();
+        paths.sort();
+        paths.dedup();
+        paths
+    }
+
+    /// Extracts the archive into `dir` and opens the app bundle.
+    ///
+    /// Unix permissions and symbolic links are restored, and entries that would be
+    /// written outside of `dir` are rejected.
+    pub fn extract(&mut self, dir: impl AsRef<Path>) -> Result<Bundle> {
+        self.archive.extract(dir.as_ref())?;
+        Bundle::open(dir.as_ref().join(self.app_dir.trim_end_matches('/')))
+    }
+}
+
+/// Packages an app bundle as an IPA archive.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IpaWriter {
+    /// The `.app` directory to package.
+    pub app: PathBuf,
+    /// The directory to package as `SwiftSupport`.
+    pub swift_support: Option<PathBuf>,
+    /// The directory to package as `Symbols`.
+    pub symbols: Option<PathBuf>,
+}
+
+impl IpaWriter {
+    /// Creates a writer packaging `app` without `SwiftSupport` or `Symbols`.
+    pub fn new(app: impl Into<PathBuf>) -> Self {
+        Self {
+            app: app.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Writes the IPA file to `path`.
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        self.write_to(File::create(path)?)?;
+        Ok(())
+    }
+
+    /// Writes the IPA archive to `writer` and returns it.
+    ///
+    /// Entries are sorted by path and have a fixed modification time, so that packaging
+    /// the same app twice gives the same archive.
+    pub fn write_to<W: Write + Seek>(&self, writer: W) -> Result<W> {
+        let app_name = self
+            .app
+            .file_name()
+            .and_then(|name| name.to_str())
+            .filter(|name| name.ends_with(".app"))
+            .ok_or_else(|| Error::InvalidBundle(format!("{} is not an app", self.app.display())))?;
+        let mut zip = ZipWriter::new(writer);
+        let options = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .last_modified_time(DateTime::default());
+        zip.add_directory("Payload/", options.unix_permissions(0o755))?;
+        let directories = [
+            (Some(&self.app), format!("Payload/{}", app_name)),
+            (self.swift_support.as_ref(), "SwiftSupport".to_owned()),
+            (self.symbols.as_ref(), "Symbols".to_owned()),
+        ];
+        for (source, name) in directories {
+            if let Some(source) = source {
+                add_dir(&mut zip, source, &name, options)?;
+            }
+        }
+        Ok(zip.finish()?)
+    }
+}
+
+fn add_dir<W: Write + Seek>(
+    zip: &mut ZipWriter<W>,
+    source: &Path,
+    name: &str,
+    options: SimpleFileOptions,
+) -> Result<()> {
+    zip.add_directory(format!("{}/", name), options.unix_permissions(0o755))?;
+    let mut entries = std::fs::read_dir(source)?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<Result<Vec<_>>>()?;
+    entries.sort();
+    for path in entries {
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        let entry_name = format!("{}/{}", name, file_name);
+        let metadata = std::fs::symlink_metadata(&path)?;
+        if metadata.file_type().is_symlink() {
+            let target = std::fs::read_link(&path)?;
+            zip.add_symlink(entry_name, target.to_string_lossy(), options)?;
+        } else if metadata.is_dir() {
+            add_dir(zip, &path, &entry_name, options)?;
+        } else {
+            zip.start_file(entry_name, options.unix_permissions(mode(&metadata)))?;
+            std::io::copy(&mut File::open(&path)?, zip)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn mode(metadata: &std::fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o777
+}
+
+#[cfg(not(unix))]
+fn mode(_metadata: &std::fs::Metadata) -> u32 {
+    0o644
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bundle::writer::BundleWriter, platform::Platform, prelude::*};
+    use std::io::Cursor;
+
+    #[test]
+    fn writes_and_reads_ipa() {
+        let dir = tempfile::tempdir().unwrap();
+        let executable = dir.path().join("Example");
+        std::fs::write(&executable, b"binary").unwrap();
+        let info_plist = InfoPlist {
+            identification: Identification {
+                bundle_identifier: "com.example.app".to_owned(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let widget = dir.path().join("Widget.appex");
+        std::fs::create_dir_all(&widget).unwrap();
+        plist::to_file_xml(widget.join("Info.plist"), &info_plist).unwrap();
+        let mut writer = BundleWriter::new(info_plist.clone(), &executable, Platform::Ios);
+        writer.plugins.push(widget);
+        let app = dir.path().join("Example.app");
+        writer.write(&app).unwrap();
+        let symbols = dir.path().join("Symbols");
+        std::fs::create_dir_all(&symbols).unwrap();
+        std::fs::write(symbols.join("Example.symbols"), b"symbols").unwrap();
+
+        let mut ipa = IpaWriter::new(&app);
+        ipa.symbols = Some(symbols);
+        let data = ipa.write_to(Cursor::new(Vec::new())).unwrap().into_inner();
+        let again = ipa.write_to(Cursor::new(Vec::new())).unwrap().into_inner();
+        assert_eq!(data, again);
+
+        let mut reader = IpaReader::new(Cursor::new(data)).unwrap();
+        assert_eq!(reader.app_dir(), "Payload/Example.app/");
+        assert_eq!(
+            reader.file_names(),
+            [
+                "Payload/",
+                "Payload/Example.app/",
+                "Payload/Example.app/Example",
+                "Payload/Example.app/Info.plist",
+                "Payload/Example.app/PkgInfo",
+                "Payload/Example.app/PlugIns/",
+                "Payload/Example.app/PlugIns/Widget.appex/",
+                "Payload/Example.app/PlugIns/Widget.appex/Info.plist",
+                "Symbols/",
+                "Symbols/Example.symbols",
+            ]
+        );
+        assert_eq!(
+            reader.info_plist().unwrap().identification,
+            info_plist.identification
+        );
+        assert!(reader.provisioning_profile().unwrap().is_none());
+        assert_eq!(reader.nested_bundle_paths(), ["PlugIns/Widget.appex"]);
+
+        let extracted = tempfile::tempdir().unwrap();
+        let bundle = reader.extract(extracted.path()).unwrap();
+        assert_eq!(bundle.nested_bundles().unwrap().len(), 1);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let metadata = std::fs::metadata(bundle.executable_path().unwrap()).unwrap();
+            assert_eq!(metadata.permissions().mode() & 0o777, 0o755);
+        }
+    }
+}
@@ -11,10 +11,49 @@
 //!
 //! Official documentation: <https://developer.apple.com/documentation/bundleresources>
 
+/// Apple App Site Association
+pub mod app_site_association;
+/// Bundle
+#[cfg(feature = "plist")]
+pub mod bundle;
+/// Cargo Manifest
+#[cfg(feature = "toml")]
+pub mod cargo_manifest;
+/// Code Signing
+#[cfg(feature = "plist")]
+pub mod code_signing;
 /// Entitlements
 pub mod entitlements;
+/// Errors
+pub mod error;
 /// Information Property List
 pub mod info_plist;
+/// IPA Archive
+#[cfg(feature = "zip")]
+pub mod ipa;
+/// Mach-O
+#[cfg(feature = "plist")]
+pub mod macho;
+/// OpenStep
+#[cfg(feature = "plist")]
+pub mod openstep;
+/// Platforms
+pub mod platform;
+/// Property List Diff
+#[cfg(feature = "plist")]
+pub mod plist_diff;
+/// Property List Document
+#[cfg(feature = "plist")]
+pub mod plist_document;
+/// Provisioning Profile
+#[cfg(feature = "plist")]
+pub mod provisioning_profile;
+/// Xcode
+#[cfg(feature = "plist")]
+pub mod xcode;
+
+#[cfg(feature = "plist")]
+mod der;
 /// Prelude
 #[allow(ambiguous_glob_reexports)]
 pub mod prelude {
@@ -23,6 +62,7 @@ pub mod prelude {
     #[cfg(feature = "plist")]
     pub use plist;
 }
+pub use error::{Error, Result};
 #[cfg(feature = "plist")]
 pub use plist::{
     self, from_bytes, from_file, from_reader, from_reader_xml, to_file_binary, to_file_xml,
@@ -137,8 +177,9 @@ mod tests {
                 ..Default::default()
             },
             categorization: Categorization {
-                bundle_package_type: Some("APPL".to_owned()),
+                bundle_package_type: Some(BundlePackageType::App),
                 application_category_type: Some(AppCategoryType::Business),
+                ..Default::default()
             },
             launch_interface: LaunchInterface {
                 launch_storyboard_name: Some("LaunchScreen".to_owned()),
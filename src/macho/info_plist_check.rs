@@ -0,0 +1,391 @@
+//! # Info.plist Check.
+//!
+//! Cross-checking the executable of a bundle against its information property list.
+//!
+//! App Store Connect rejects uploads where the deployment target or platform recorded
+//! in the load commands of the executable disagree with `MinimumOSVersion` or
+//! `LSMinimumSystemVersion`, where `CFBundleExecutable` names a missing file, or where
+//! `UIRequiredDeviceCapabilities` doesn't match the architectures of the executable.
+
+use super::MachO;
+use crate::{
+    error::Result,
+    info_plist::{
+        app_execution::{ArchitecturePriority, DeviceCapabilities},
+        InfoPlist,
+    },
+    platform::Platform,
+};
+use std::{fmt, path::Path};
+
+/// A disagreement between an executable and the information property list of its
+/// bundle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LaunchMismatch {
+    /// `CFBundleExecutable` isn't set.
+    MissingBundleExecutable,
+    /// `CFBundleExecutable` names a file that doesn't exist.
+    MissingExecutable { name: String },
+    /// An architecture of the executable has no `LC_BUILD_VERSION` or
+    /// `LC_VERSION_MIN_*` load command.
+    MissingBuildVersion { architecture: String },
+    /// The executable is built for a platform that the minimum OS version key of the
+    /// property list doesn't apply to, like `LSMinimumSystemVersion` for an iOS
+    /// executable.
+    Platform {
+        architecture: String,
+        platform: Platform,
+        key: &'static str,
+    },
+    /// The minimum OS version key that the platform of the executable needs is missing.
+    MissingMinimumVersion {
+        architecture: String,
+        key: &'static str,
+        deployment_target: String,
+    },
+    /// The minimum OS version of the property list differs from the deployment target
+    /// recorded in the executable.
+    MinimumVersion {
+        architecture: String,
+        key: &'static str,
+        info_plist: String,
+        deployment_target: String,
+    },
+    /// `UIRequiredDeviceCapabilities` doesn't list `arm64` although the executable only
+    /// runs on 64-bit devices and the deployment target allows 32-bit ones.
+    MissingArm64Capability,
+    /// `UIRequiredDeviceCapabilities` requires an architecture the executable isn't
+    /// built for.
+    RequiredArchitecture { architecture: String },
+    /// `LSArchitecturePriority` names an architecture the executable isn't built for.
+    ArchitecturePriority { architecture: String },
+}
+
+impl fmt::Display for LaunchMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingBundleExecutable => write!(f, "CFBundleExecutable is not set"),
+            Self::MissingExecutable { name } => {
+                write!(f, "CFBundleExecutable `{}` does not exist", name)
+            }
+            Self::MissingBuildVersion { architecture } => {
+                write!(
+                    f,
+                    "{} slice has no build version load command",
+                    architecture
+                )
+            }
+            Self::Platform {
+                architecture,
+                platform,
+                key,
+            } => write!(
+                f,
+                "{} slice is built for {}, which does not use {}",
+                architecture, platform, key
+            ),
+            Self::MissingMinimumVersion {
+                architecture,
+                key,
+                deployment_target,
+            } => write!(
+                f,
+                "{} is not set, but the {} slice targets {}",
+                key, architecture, deployment_target
+            ),
+            Self::MinimumVersion {
+                architecture,
+                key,
+                info_plist,
+                deployment_target,
+            } => write!(
+                f,
+                "{} is {}, but the {} slice targets {}",
+                key, info_plist, architecture, deployment_target
+            ),
+            Self::MissingArm64Capability => write!(
+                f,
+                "UIRequiredDeviceCapabilities must contain arm64 for a 64-bit only executable"
+            ),
+            Self::RequiredArchitecture { architecture } => write!(
+                f,
+                "UIRequiredDeviceCapabilities requires {}, but the executable has no {} slice",
+                architecture, architecture
+            ),
+            Self::ArchitecturePriority { architecture } => write!(
+                f,
+                "LSArchitecturePriority prefers {}, but the executable has no {} slice",
+                architecture, architecture
+            ),
+        }
+    }
+}
+
+/// Reads the executable named by `CFBundleExecutable` from `executable_dir`, like
+/// `Contents/MacOS` of a macOS app or the root of an iOS app, and checks it against
+/// the property list.
+pub fn check_executable(
+    info_plist: &InfoPlist,
+    executable_dir: impl AsRef<Path>,
+) -> Result<Vec<LaunchMismatch>> {
+    let name = match &info_plist.launch.bundle_executable {
+        Some(name) => name,
+        None => return Ok(vec![LaunchMismatch::MissingBundleExecutable]),
+    };
+    let path = executable_dir.as_ref().join(name);
+    if !path.is_file() {
+        return Ok(vec![LaunchMismatch::MissingExecutable {
+            name: name.clone(),
+        }]);
+    }
+    let data = std::fs::read(path)?;
+    Ok(check_info_plist(info_plist, &MachO::parse(&data)?))
+}
+
+/// Checks the architectures and build version load commands of an executable against
+/// the `OperatingSystemVersion`, `Launch` and `LaunchConditions` keys of a property
+/// list.
+pub fn check_info_plist(info_plist: &InfoPlist, slices: &[MachO<'_>]) -> Vec<LaunchMismatch> {
+    let mut mismatches = Vec::new();
+    let os_version = &info_plist.operating_system_version;
+    let architectures: Vec<_> = slices.iter().map(architecture).collect();
+    let mut uses_capabilities = false;
+    let mut minimum_ios = None;
+    for (slice, architecture) in slices.iter().zip(&architectures) {
+        if slice.build_versions.is_empty() {
+            mismatches.push(LaunchMismatch::MissingBuildVersion {
+                architecture: architecture.clone(),
+            });
+        }
+        for build_version in &slice.build_versions {
+            let platform = match build_version.platform() {
+                Some(platform) => platform,
+                None => continue,
+            };
+            let (key, expected, other_key, other) = match platform {
+                Platform::MacOs => {
+                    let by_architecture = os_version
+                        .minimum_system_version_by_architecture
+                        .as_ref()
+                        .filter(|_| architecture == "x86_64")
+                        .map(|versions| &versions.x86_64);
+                    (
+                        "LSMinimumSystemVersion",
+                        by_architecture.or(os_version.minimum_system_version.as_ref()),
+                        "MinimumOSVersion",
+                        os_version.minimum_os_version.as_ref(),
+                    )
+                }
+                _ => (
+                    "MinimumOSVersion",
+                    os_version.minimum_os_version.as_ref(),
+                    "LSMinimumSystemVersion",
+                    os_version.minimum_system_version.as_ref(),
+                ),
+            };
+            if other.is_some() && expected.is_none() {
+                mismatches.push(LaunchMismatch::Platform {
+                    architecture: architecture.clone(),
+                    platform,
+                    key: other_key,
+                });
+                continue;
+            }
+            let deployment_target = format_version(build_version.minimum_os);
+            match expected {
+                None => mismatches.push(LaunchMismatch::MissingMinimumVersion {
+                    architecture: architecture.clone(),
+                    key,
+                    deployment_target,
+                }),
+                Some(version) if parse_version(version) != Some(build_version.minimum_os) => {
+                    mismatches.push(LaunchMismatch::MinimumVersion {
+                        architecture: architecture.clone(),
+                        key,
+                        info_plist: version.clone(),
+                        deployment_target,
+                    })
+                }
+                Some(_) => {}
+            }
+            if platform != Platform::MacOs {
+                uses_capabilities = true;
+            }
+            if platform == Platform::Ios && !build_version.is_simulator() {
+                minimum_ios = Some(build_version.minimum_os);
+            }
+        }
+    }
+
+    let launch_conditions = &info_plist.launch_conditions;
+    let capabilities = launch_conditions
+        .required_device_capabilities
+        .as_deref()
+        .unwrap_or_default();
+    let has = |name: &str| {
+        architectures
+            .iter()
+            .any(|architecture| architecture == name)
+    };
+    if uses_capabilities {
+        let arm64_only = !architectures.is_empty()
+            && architectures
+                .iter()
+                .all(|architecture| architecture.starts_with("arm64"));
+        // iOS 11 and later only run on 64-bit devices, so the capability is implied.
+        if arm64_only
+            && minimum_ios.is_some_and(|version| version < (11, 0, 0))
+            && !capabilities.contains(&DeviceCapabilities::Arm64)
+        {
+            mismatches.push(LaunchMismatch::MissingArm64Capability);
+        }
+        for (capability, architecture) in [
+            (DeviceCapabilities::Arm64, "arm64"),
+            (DeviceCapabilities::Armv7, "armv7"),
+        ] {
+            if capabilities.contains(&capability) && !has(architecture) {
+                let architecture = architecture.to_owned();
+                mismatches.push(LaunchMismatch::RequiredArchitecture { architecture });
+            }
+        }
+    }
+    if let Some(priority) = &launch_conditions.architecture_priority {
+        let architecture = match priority {
+            ArchitecturePriority::I386 => "i386",
+            ArchitecturePriority::X86_64 => "x86_64",
+            ArchitecturePriority::Arm64 => "arm64",
+            ArchitecturePriority::Arm64e => "arm64e",
+        };
+        if !has(architecture) {
+            let architecture = architecture.to_owned();
+            mismatches.push(LaunchMismatch::ArchitecturePriority { architecture });
+        }
+    }
+    mismatches
+}
+
+fn architecture(slice: &MachO<'_>) -> String {
+    match slice.architecture() {
+        Some(architecture) => architecture.to_owned(),
+        None => format!("cpu type {:#x}", slice.cpu_type),
+    }
+}
+
+/// Parses a `major[.minor[.patch]]` version.
+fn parse_version(version: &str) -> Option<(u16, u8, u8)> {
+    let mut components = version.trim().split('.');
+    let major = components.next()?.parse().ok()?;
+    let minor = components.next().map_or(Ok(0), str::parse).ok()?;
+    let patch = components.next().map_or(Ok(0), str::parse).ok()?;
+    match components.next() {
+        Some(_) => None,
+        None => Some((major, minor, patch)),
+    }
+}
+
+fn format_version((major, minor, patch): (u16, u8, u8)) -> String {
+    match patch {
+        0 => format!("{}.{}", major, minor),
+        _ => format!("{}.{}.{}", major, minor, patch),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        macho::{info_plist_section::InfoPlistSection, BuildVersion, CPU_TYPE_ARM64},
+        prelude::*,
+    };
+
+    fn object(platform: u32, minimum_os: (u16, u8, u8)) -> Vec<u8> {
+        let build_version = BuildVersion {
+            platform,
+            minimum_os,
+            sdk: (17, 0, 0),
+        };
+        InfoPlistSection::new(&InfoPlist::default())
+            .unwrap()
+            .object_file(CPU_TYPE_ARM64, build_version)
+    }
+
+    #[test]
+    fn reports_version_and_capability_mismatches() {
+        let mut info_plist = InfoPlist {
+            operating_system_version: OperatingSystemVersion {
+                minimum_os_version: Some("10.3".to_owned()),
+                ..Default::default()
+            },
+            launch_conditions: LaunchConditions {
+                required_device_capabilities: Some(vec![DeviceCapabilities::Armv7]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let data = object(BuildVersion::PLATFORM_IOS, (10, 3, 0));
+        let slices = MachO::parse(&data).unwrap();
+        assert_eq!(
+            check_info_plist(&info_plist, &slices),
+            [
+                LaunchMismatch::MissingArm64Capability,
+                LaunchMismatch::RequiredArchitecture {
+                    architecture: "armv7".to_owned()
+                },
+            ]
+        );
+
+        info_plist.operating_system_version.minimum_os_version = Some("12".to_owned());
+        info_plist.launch_conditions.required_device_capabilities =
+            Some(vec![DeviceCapabilities::Arm64]);
+        let data = object(BuildVersion::PLATFORM_IOS, (12, 0, 0));
+        let slices = MachO::parse(&data).unwrap();
+        assert_eq!(check_info_plist(&info_plist, &slices), []);
+
+        let data = object(BuildVersion::PLATFORM_IOS, (13, 0, 0));
+        let slices = MachO::parse(&data).unwrap();
+        let mismatch = &check_info_plist(&info_plist, &slices)[0];
+        assert_eq!(
+            mismatch.to_string(),
+            "MinimumOSVersion is 12, but the arm64 slice targets 13.0"
+        );
+    }
+
+    #[test]
+    fn reports_platform_and_missing_executable() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut info_plist = InfoPlist {
+            operating_system_version: OperatingSystemVersion {
+                minimum_os_version: Some("14.0".to_owned()),
+                ..Default::default()
+            },
+            launch: Launch {
+                bundle_executable: Some("App".to_owned()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert_eq!(
+            check_executable(&info_plist, dir.path()).unwrap(),
+            [LaunchMismatch::MissingExecutable {
+                name: "App".to_owned()
+            }]
+        );
+
+        let data = object(BuildVersion::PLATFORM_MACOS, (11, 0, 0));
+        std::fs::write(dir.path().join("App"), data).unwrap();
+        assert_eq!(
+            check_executable(&info_plist, dir.path()).unwrap(),
+            [LaunchMismatch::Platform {
+                architecture: "arm64".to_owned(),
+                platform: Platform::MacOs,
+                key: "MinimumOSVersion",
+            }]
+        );
+
+        info_plist.operating_system_version = OperatingSystemVersion {
+            minimum_system_version: Some("11.0.0".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(check_executable(&info_plist, dir.path()).unwrap(), []);
+    }
+}
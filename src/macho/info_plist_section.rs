@@ -0,0 +1,176 @@
+//! # Info.plist Section.
+//!
+//! Embedding an information property list in a command-line tool.
+//!
+//! Executables that don't live in a bundle, like command-line tools and launch daemons,
+//! carry their information property list in the `__info_plist` section of the `__TEXT`
+//! segment. The system reads it to identify the executable, for example when asking
+//! the user for access to protected resources or when registering the executable with
+//! `SMAppService`.
+//!
+//! From a `build.rs` script, either pass the section to the linker:
+//!
+//! ```no_run
+//! # use apple_bundle::{macho::info_plist_section::InfoPlistSection, prelude::*};
+//! let info_plist = InfoPlist {
+//!     identification: Identification {
+//!         bundle_identifier: "com.example.daemon".to_owned(),
+//!         ..Default::default()
+//!     },
+//!     ..Default::default()
+//! };
+//! let out_dir = std::env::var("OUT_DIR").unwrap();
+//! InfoPlistSection::new(&info_plist)
+//!     .unwrap()
+//!     .emit_cargo_link_args(out_dir)
+//!     .unwrap();
+//! ```
+//!
+//! or link an object file holding the section, built with
+//! [`InfoPlistSection::object_file`].
+
+use super::{BuildVersion, LC_BUILD_VERSION, LC_SEGMENT_64, MH_MAGIC_64};
+use crate::{error::Result, info_plist::InfoPlist};
+use std::path::{Path, PathBuf};
+
+const MH_OBJECT: u32 = 0x1;
+const S_ATTR_NO_DEAD_STRIP: u32 = 0x1000_0000;
+
+/// The contents of the `__TEXT,__info_plist` section of an executable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InfoPlistSection {
+    /// The XML property list stored in the section.
+    pub data: Vec<u8>,
+}
+
+impl InfoPlistSection {
+    pub const SEGMENT_NAME: &'static str = "__TEXT";
+    pub const SECTION_NAME: &'static str = "__info_plist";
+
+    /// Serializes the information property list as the section contents.
+    pub fn new(info_plist: &InfoPlist) -> Result<Self> {
+        let mut data = Vec::new();
+        plist::to_writer_xml(&mut data, info_plist)?;
+        data.push(b'\n');
+        Ok(Self { data })
+    }
+
+    /// Returns the linker arguments that create the section from the file at `path`.
+    pub fn linker_args(path: impl AsRef<Path>) -> Vec<String> {
+        vec![
+            "-sectcreate".to_owned(),
+            Self::SEGMENT_NAME.to_owned(),
+            Self::SECTION_NAME.to_owned(),
+            path.as_ref().display().to_string(),
+        ]
+    }
+
+    /// Writes the section contents to `Info.plist` in `out_dir` and prints the cargo
+    /// instructions that pass it to the linker of the package binaries.
+    ///
+    /// Meant to be called from a `build.rs` script, which should print
+    /// `cargo:rerun-if-changed` for the files the information property list is built
+    /// from. Returns the path of the written file.
+    pub fn emit_cargo_link_args(&self, out_dir: impl AsRef<Path>) -> Result<PathBuf> {
+        let path = out_dir.as_ref().join("Info.plist");
+        std::fs::write(&path, &self.data)?;
+        for arg in Self::linker_args(&path) {
+            println!("cargo:rustc-link-arg-bins={}", arg);
+        }
+        Ok(path)
+    }
+
+    /// Builds a relocatable 64-bit Mach-O object file holding the section, for the
+    /// given CPU type, like [`CPU_TYPE_ARM64`](super::CPU_TYPE_ARM64) or
+    /// [`CPU_TYPE_X86_64`](super::CPU_TYPE_X86_64), and platform.
+    ///
+    /// The section is marked so that dead code stripping keeps it.
+    pub fn object_file(&self, cpu_type: u32, build_version: BuildVersion) -> Vec<u8> {
+        let segment_size = 72 + 80;
+        let build_version_size = 24;
+        let commands_size = segment_size + build_version_size;
+        let data_offset = 32 + commands_size;
+        let size = self.data.len() as u64;
+        let pack = |(major, minor, patch): (u16, u8, u8)| {
+            (major as u32) << 16 | (minor as u32) << 8 | patch as u32
+        };
+
+        let mut file = Vec::with_capacity(data_offset as usize + self.data.len());
+        let header = [MH_MAGIC_64, cpu_type, 0, MH_OBJECT, 2, commands_size, 0, 0];
+        header
+            .iter()
+            .for_each(|value| file.extend(value.to_le_bytes()));
+        // Object files put every section in a single unnamed segment.
+        file.extend(LC_SEGMENT_64.to_le_bytes());
+        file.extend(segment_size.to_le_bytes());
+        file.extend([0; 16]);
+        [0, size, data_offset as u64, size]
+            .iter()
+            .for_each(|value| file.extend(value.to_le_bytes()));
+        [7u32, 7, 1, 0]
+            .iter()
+            .for_each(|value| file.extend(value.to_le_bytes()));
+        file.extend(name(Self::SECTION_NAME));
+        file.extend(name(Self::SEGMENT_NAME));
+        file.extend(0u64.to_le_bytes());
+        file.extend(size.to_le_bytes());
+        [data_offset, 0, 0, 0, S_ATTR_NO_DEAD_STRIP, 0, 0, 0]
+            .iter()
+            .for_each(|value| file.extend(value.to_le_bytes()));
+        let build_version = [
+            LC_BUILD_VERSION,
+            build_version_size,
+            build_version.platform,
+            pack(build_version.minimum_os),
+            pack(build_version.sdk),
+            0,
+        ];
+        build_version
+            .iter()
+            .for_each(|value| file.extend(value.to_le_bytes()));
+        file.extend_from_slice(&self.data);
+        file
+    }
+}
+
+fn name(name: &str) -> [u8; 16] {
+    let mut bytes = [0; 16];
+    bytes[..name.len()].copy_from_slice(name.as_bytes());
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        macho::{MachO, CPU_TYPE_ARM64},
+        prelude::*,
+    };
+
+    #[test]
+    fn builds_object_file_with_section() {
+        let info_plist = InfoPlist {
+            identification: Identification {
+                bundle_identifier: "com.example.daemon".to_owned(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let section = InfoPlistSection::new(&info_plist).unwrap();
+        let build_version = BuildVersion {
+            platform: BuildVersion::PLATFORM_MACOS,
+            minimum_os: (11, 0, 0),
+            sdk: (14, 0, 0),
+        };
+        let object = section.object_file(CPU_TYPE_ARM64, build_version);
+        let macho = MachO::parse(&object).unwrap().remove(0);
+        assert_eq!(macho.file_type, MH_OBJECT);
+        assert_eq!(macho.architecture(), Some("arm64"));
+        assert_eq!(macho.build_versions, [build_version]);
+        assert_eq!(macho.info_plist().unwrap(), Some(info_plist));
+        assert_eq!(
+            InfoPlistSection::linker_args("/tmp/Info.plist"),
+            ["-sectcreate", "__TEXT", "__info_plist", "/tmp/Info.plist"]
+        );
+    }
+}
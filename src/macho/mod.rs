@@ -0,0 +1,610 @@
+//! # Mach-O.
+//!
+//! The executable format of Apple platforms.
+//!
+//! A Mach-O file starts with a header followed by load commands describing its
+//! segments, the linked libraries, the platform it was built for and the location of
+//! its code signature. Universal binaries bundle several Mach-O files, one per
+//! architecture, behind a fat header. Code signing embeds the entitlements of the
+//! executable in its signature, and command-line tools that aren't in a bundle carry
+//! their information property list in the `__TEXT,__info_plist` section.
+//!
+//! Official documentation: <https://developer.apple.com/documentation/kernel/mach_header>
+
+pub mod info_plist_check;
+pub mod info_plist_section;
+
+use crate::{
+    code_signing::der_entitlements::entitlements_from_der,
+    entitlements::Entitlements,
+    error::{Error, Result},
+    info_plist::InfoPlist,
+    platform::Platform,
+};
+use std::{ops::Range, path::Path};
+
+const FAT_MAGIC: u32 = 0xcafe_babe;
+const FAT_MAGIC_64: u32 = 0xcafe_babf;
+const MH_MAGIC: u32 = 0xfeed_face;
+const MH_MAGIC_64: u32 = 0xfeed_facf;
+
+const LC_SEGMENT: u32 = 0x1;
+const LC_SEGMENT_64: u32 = 0x19;
+const LC_CODE_SIGNATURE: u32 = 0x1d;
+const LC_VERSION_MIN_MACOSX: u32 = 0x24;
+const LC_VERSION_MIN_IPHONEOS: u32 = 0x25;
+const LC_VERSION_MIN_TVOS: u32 = 0x2f;
+const LC_VERSION_MIN_WATCHOS: u32 = 0x30;
+const LC_BUILD_VERSION: u32 = 0x32;
+
+const CSMAGIC_EMBEDDED_SIGNATURE: u32 = 0xfade_0cc0;
+const CSMAGIC_CODEDIRECTORY: u32 = 0xfade_0c02;
+const CSMAGIC_EMBEDDED_ENTITLEMENTS: u32 = 0xfade_7171;
+const CSMAGIC_EMBEDDED_DER_ENTITLEMENTS: u32 = 0xfade_7172;
+const CSSLOT_CODEDIRECTORY: u32 = 0;
+const CSSLOT_ENTITLEMENTS: u32 = 5;
+const CSSLOT_DER_ENTITLEMENTS: u32 = 7;
+
+/// CPU type of 64-bit ARM processors.
+pub const CPU_TYPE_ARM64: u32 = 0x0100_000c;
+/// CPU type of 32-bit ARM processors.
+pub const CPU_TYPE_ARM: u32 = 0xc;
+/// CPU type of 64-bit Intel processors.
+pub const CPU_TYPE_X86_64: u32 = 0x0100_0007;
+/// CPU type of 32-bit Intel processors.
+pub const CPU_TYPE_X86: u32 = 0x7;
+
+/// A thin Mach-O file or one architecture of a universal binary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MachO<'a> {
+    data: &'a [u8],
+    /// The CPU type, like [`CPU_TYPE_ARM64`].
+    pub cpu_type: u32,
+    /// The CPU subtype, without the capability bits.
+    pub cpu_subtype: u32,
+    /// The file type, like `MH_EXECUTE` (`0x2`) or `MH_DYLIB` (`0x6`).
+    pub file_type: u32,
+    /// Whether the file uses the 64-bit layout.
+    pub is_64: bool,
+    /// The sections of all segments.
+    pub sections: Vec<Section>,
+    /// The platforms and OS versions the file was built for.
+    pub build_versions: Vec<BuildVersion>,
+    code_signature: Option<Range<usize>>,
+}
+
+/// A section of a Mach-O segment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    /// The name of the segment, like `__TEXT`.
+    pub segment_name: String,
+    /// The name of the section, like `__info_plist`.
+    pub section_name: String,
+    /// The offset of the section contents in the Mach-O file, or `0` for zero-fill
+    /// sections.
+    pub offset: u32,
+    /// The size of the section contents.
+    pub size: u64,
+}
+
+/// The platform and OS versions recorded by an `LC_BUILD_VERSION` or
+/// `LC_VERSION_MIN_*` load command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BuildVersion {
+    /// The platform identifier, like [`BuildVersion::PLATFORM_IOS`].
+    pub platform: u32,
+    /// The minimum OS version, as `(major, minor, patch)`.
+    pub minimum_os: (u16, u8, u8),
+    /// The SDK version, as `(major, minor, patch)`.
+    pub sdk: (u16, u8, u8),
+}
+
+impl BuildVersion {
+    pub const PLATFORM_MACOS: u32 = 1;
+    pub const PLATFORM_IOS: u32 = 2;
+    pub const PLATFORM_TVOS: u32 = 3;
+    pub const PLATFORM_WATCHOS: u32 = 4;
+    pub const PLATFORM_MACCATALYST: u32 = 6;
+    pub const PLATFORM_IOSSIMULATOR: u32 = 7;
+    pub const PLATFORM_TVOSSIMULATOR: u32 = 8;
+    pub const PLATFORM_WATCHOSSIMULATOR: u32 = 9;
+    pub const PLATFORM_DRIVERKIT: u32 = 10;
+    pub const PLATFORM_VISIONOS: u32 = 11;
+    pub const PLATFORM_VISIONOSSIMULATOR: u32 = 12;
+
+    /// Returns the platform, counting simulators as the platform they simulate, or
+    /// `None` for Mac Catalyst, DriverKit and unknown platforms.
+    pub fn platform(&self) -> Option<Platform> {
+        Some(match self.platform {
+            Self::PLATFORM_MACOS => Platform::MacOs,
+            Self::PLATFORM_IOS | Self::PLATFORM_IOSSIMULATOR => Platform::Ios,
+            Self::PLATFORM_TVOS | Self::PLATFORM_TVOSSIMULATOR => Platform::TvOs,
+            Self::PLATFORM_WATCHOS | Self::PLATFORM_WATCHOSSIMULATOR => Platform::WatchOs,
+            Self::PLATFORM_VISIONOS | Self::PLATFORM_VISIONOSSIMULATOR => Platform::VisionOs,
+            _ => return None,
+        })
+    }
+
+    /// Whether the platform is a simulator.
+    pub fn is_simulator(&self) -> bool {
+        matches!(
+            self.platform,
+            Self::PLATFORM_IOSSIMULATOR
+                | Self::PLATFORM_TVOSSIMULATOR
+                | Self::PLATFORM_WATCHOSSIMULATOR
+                | Self::PLATFORM_VISIONOSSIMULATOR
+        )
+    }
+
+    fn unpack(version: u32) -> (u16, u8, u8) {
+        ((version >> 16) as u16, (version >> 8) as u8, version as u8)
+    }
+}
+
+impl<'a> MachO<'a> {
+    /// Parses a thin Mach-O file or every architecture of a universal binary.
+    pub fn parse(data: &'a [u8]) -> Result<Vec<MachO<'a>>> {
+        let magic = Bytes::big(data).u32(0)?;
+        if magic != FAT_MAGIC && magic != FAT_MAGIC_64 {
+            return Ok(vec![Self::parse_thin(data)?]);
+        }
+        let fat = Bytes::big(data);
+        let count = fat.u32(4)? as usize;
+        let arch_size = match magic {
+            FAT_MAGIC => 20,
+            _ => 32,
+        };
+        if count.saturating_mul(arch_size) > data.len() - 8 {
+            return Err(invalid("architectures extend past the end of the file"));
+        }
+        let mut slices = Vec::with_capacity(count);
+        for index in 0..count {
+            let (offset, size) = match magic {
+                FAT_MAGIC => {
+                    let arch = 8 + index * arch_size;
+                    (fat.u32(arch + 8)? as usize, fat.u32(arch + 12)? as usize)
+                }
+                _ => {
+                    let arch = 8 + index * arch_size;
+                    (fat.u64(arch + 8)? as usize, fat.u64(arch + 16)? as usize)
+                }
+            };
+            let slice = offset
+                .checked_add(size)
+                .and_then(|end| data.get(offset..end))
+                .ok_or_else(|| invalid("architecture extends past the end of the file"))?;
+            slices.push(Self::parse_thin(slice)?);
+        }
+        Ok(slices)
+    }
+
+    fn parse_thin(data: &'a [u8]) -> Result<Self> {
+        let bytes = match Bytes::little(data).u32(0)? {
+            MH_MAGIC | MH_MAGIC_64 => Bytes::little(data),
+            _ => match Bytes::big(data).u32(0)? {
+                MH_MAGIC | MH_MAGIC_64 => Bytes::big(data),
+                _ => return Err(invalid("not a Mach-O file")),
+            },
+        };
+        let is_64 = bytes.u32(0)? == MH_MAGIC_64;
+        let mut macho = Self {
+            data,
+            cpu_type: bytes.u32(4)?,
+            cpu_subtype: bytes.u32(8)? & 0x00ff_ffff,
+            file_type: bytes.u32(12)?,
+            is_64,
+            sections: Vec::new(),
+            build_versions: Vec::new(),
+            code_signature: None,
+        };
+        let count = bytes.u32(16)?;
+        let mut offset = if is_64 { 32 } else { 28 };
+        for _ in 0..count {
+            let (cmd, size) = (bytes.u32(offset)?, bytes.u32(offset + 4)? as usize);
+            if size < 8 {
+                return Err(invalid("load command is too small"));
+            }
+            match cmd {
+                LC_SEGMENT | LC_SEGMENT_64 => {
+                    let is_64 = cmd == LC_SEGMENT_64;
+                    let (count_at, first, section_size) = match is_64 {
+                        true => (offset + 64, offset + 72, 80),
+                        false => (offset + 48, offset + 56, 68),
+                    };
+                    for index in 0..bytes.u32(count_at)? as usize {
+                        let section = first + index * section_size;
+                        let (size, file_offset) = match is_64 {
+                            true => (bytes.u64(section + 40)?, bytes.u32(section + 48)?),
+                            false => (bytes.u32(section + 36)? as u64, bytes.u32(section + 40)?),
+                        };
+                        macho.sections.push(Section {
+                            section_name: bytes.name(section)?,
+                            segment_name: bytes.name(section + 16)?,
+                            offset: file_offset,
+                            size,
+                        });
+                    }
+                }
+                LC_CODE_SIGNATURE => {
+                    let start = bytes.u32(offset + 8)? as usize;
+                    let size = bytes.u32(offset + 12)? as usize;
+                    macho.code_signature = Some(start..start + size);
+                }
+                LC_BUILD_VERSION => macho.build_versions.push(BuildVersion {
+                    platform: bytes.u32(offset + 8)?,
+                    minimum_os: BuildVersion::unpack(bytes.u32(offset + 12)?),
+                    sdk: BuildVersion::unpack(bytes.u32(offset + 16)?),
+                }),
+                LC_VERSION_MIN_MACOSX
+                | LC_VERSION_MIN_IPHONEOS
+                | LC_VERSION_MIN_TVOS
+                | LC_VERSION_MIN_WATCHOS => macho.build_versions.push(BuildVersion {
+                    platform: match cmd {
+                        LC_VERSION_MIN_MACOSX => BuildVersion::PLATFORM_MACOS,
+                        LC_VERSION_MIN_IPHONEOS => BuildVersion::PLATFORM_IOS,
+                        LC_VERSION_MIN_TVOS => BuildVersion::PLATFORM_TVOS,
+                        _ => BuildVersion::PLATFORM_WATCHOS,
+                    },
+                    minimum_os: BuildVersion::unpack(bytes.u32(offset + 8)?),
+                    sdk: BuildVersion::unpack(bytes.u32(offset + 12)?),
+                }),
+                _ => {}
+            }
+            offset += size;
+        }
+        Ok(macho)
+    }
+
+    /// Returns the name of the architecture, like `arm64` or `x86_64`.
+    pub fn architecture(&self) -> Option<&'static str> {
+        Some(match (self.cpu_type, self.cpu_subtype) {
+            (CPU_TYPE_ARM64, 2) => "arm64e",
+            (CPU_TYPE_ARM64, _) => "arm64",
+            (CPU_TYPE_ARM, 9) => "armv7",
+            (CPU_TYPE_ARM, 11) => "armv7s",
+            (CPU_TYPE_ARM, 12) => "armv7k",
+            (CPU_TYPE_X86_64, 8) => "x86_64h",
+            (CPU_TYPE_X86_64, _) => "x86_64",
+            (CPU_TYPE_X86, _) => "i386",
+            _ => return None,
+        })
+    }
+
+    /// Returns the contents of a section, or `None` if the file has no such section.
+    pub fn section_data(&self, segment_name: &str, section_name: &str) -> Option<&'a [u8]> {
+        let section = self.sections.iter().find(|section| {
+            section.segment_name == segment_name && section.section_name == section_name
+        })?;
+        let start = section.offset as usize;
+        self.data
+            .get(start..start.checked_add(section.size as usize)?)
+    }
+
+    /// Reads the information property list from the `__TEXT,__info_plist` section.
+    pub fn info_plist(&self) -> Result<Option<InfoPlist>> {
+        match self.section_data("__TEXT", "__info_plist") {
+            Some(data) => Ok(Some(plist::from_bytes(data)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the primary code directory of the code signature, including its blob
+    /// header. Its hash is the `cdhash` that identifies the signed code.
+    pub fn code_directory(&self) -> Result<Option<&'a [u8]>> {
+        self.code_signature_blob(CSSLOT_CODEDIRECTORY, CSMAGIC_CODEDIRECTORY)
+    }
+
+    /// Returns the XML property list stored in the entitlements slot of the code
+    /// signature.
+    pub fn xml_entitlements_data(&self) -> Result<Option<&'a [u8]>> {
+        self.code_signature_slot(CSSLOT_ENTITLEMENTS, CSMAGIC_EMBEDDED_ENTITLEMENTS)
+    }
+
+    /// Returns the DER blob stored in the DER entitlements slot of the code signature.
+    pub fn der_entitlements_data(&self) -> Result<Option<&'a [u8]>> {
+        self.code_signature_slot(CSSLOT_DER_ENTITLEMENTS, CSMAGIC_EMBEDDED_DER_ENTITLEMENTS)
+    }
+
+    /// Parses the entitlements of the XML entitlements slot of the code signature.
+    pub fn xml_entitlements(&self) -> Result<Option<Entitlements>> {
+        match self.xml_entitlements_data()? {
+            Some(data) => Ok(Some(plist::from_bytes(data)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Parses the entitlements of the DER entitlements slot of the code signature.
+    pub fn der_entitlements(&self) -> Result<Option<Entitlements>> {
+        match self.der_entitlements_data()? {
+            Some(data) => Ok(Some(entitlements_from_der(data)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Parses the entitlements embedded in the code signature, preferring the XML slot
+    /// over the DER slot.
+    pub fn entitlements(&self) -> Result<Option<Entitlements>> {
+        match self.xml_entitlements()? {
+            Some(entitlements) => Ok(Some(entitlements)),
+            None => self.der_entitlements(),
+        }
+    }
+
+    fn code_signature_slot(&self, slot: u32, magic: u32) -> Result<Option<&'a [u8]>> {
+        Ok(self
+            .code_signature_blob(slot, magic)?
+            .map(|blob| &blob[8..]))
+    }
+
+    fn code_signature_blob(&self, slot: u32, magic: u32) -> Result<Option<&'a [u8]>> {
+        let range = match &self.code_signature {
+            Some(range) => range.clone(),
+            None => return Ok(None),
+        };
+        let signature = self
+            .data
+            .get(range)
+            .ok_or_else(|| invalid("code signature extends past the end of the file"))?;
+        let bytes = Bytes::big(signature);
+        if bytes.u32(0)? != CSMAGIC_EMBEDDED_SIGNATURE {
+            return Err(invalid("unknown code signature format"));
+        }
+        for index in 0..bytes.u32(8)? as usize {
+            if bytes.u32(12 + index * 8)? != slot {
+                continue;
+            }
+            let offset = bytes.u32(16 + index * 8)? as usize;
+            if bytes.u32(offset)? != magic {
+                return Err(invalid("unexpected code signature blob"));
+            }
+            let len = bytes.u32(offset + 4)? as usize;
+            let blob = Some(len)
+                .filter(|&len| len >= 8)
+                .and_then(|len| signature.get(offset..offset + len))
+                .ok_or_else(|| invalid("code signature blob is truncated"))?;
+            return Ok(Some(blob));
+        }
+        Ok(None)
+    }
+}
+
+/// Reads the entitlements embedded in the code signature of every architecture of a
+/// Mach-O file.
+pub fn read_entitlements(path: impl AsRef<Path>) -> Result<Vec<Option<Entitlements>>> {
+    let data = std::fs::read(path)?;
+    MachO::parse(&data)?
+        .iter()
+        .map(MachO::entitlements)
+        .collect()
+}
+
+/// Reads the information property list embedded in the first architecture of a Mach-O
+/// file that has one.
+pub fn read_info_plist(path: impl AsRef<Path>) -> Result<Option<InfoPlist>> {
+    let data = std::fs::read(path)?;
+    for macho in MachO::parse(&data)? {
+        if let Some(info_plist) = macho.info_plist()? {
+            return Ok(Some(info_plist));
+        }
+    }
+    Ok(None)
+}
+
+fn invalid(reason: &str) -> Error {
+    Error::InvalidMachO(reason.to_owned())
+}
+
+/// Bounds-checked reads of integers with a fixed byte order.
+#[derive(Clone, Copy)]
+struct Bytes<'a> {
+    data: &'a [u8],
+    big_endian: bool,
+}
+
+impl<'a> Bytes<'a> {
+    fn big(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            big_endian: true,
+        }
+    }
+
+    fn little(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            big_endian: false,
+        }
+    }
+
+    fn array<const N: usize>(&self, offset: usize) -> Result<[u8; N]> {
+        offset
+            .checked_add(N)
+            .and_then(|end| self.data.get(offset..end))
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| invalid("unexpected end of file"))
+    }
+
+    fn u32(&self, offset: usize) -> Result<u32> {
+        let bytes = self.array(offset)?;
+        Ok(match self.big_endian {
+            true => u32::from_be_bytes(bytes),
+            false => u32::from_le_bytes(bytes),
+        })
+    }
+
+    fn u64(&self, offset: usize) -> Result<u64> {
+        let bytes = self.array(offset)?;
+        Ok(match self.big_endian {
+            true => u64::from_be_bytes(bytes),
+            false => u64::from_le_bytes(bytes),
+        })
+    }
+
+    fn name(&self, offset: usize) -> Result<String> {
+        let bytes: [u8; 16] = self.array(offset)?;
+        let len = bytes.iter().position(|&byte| byte == 0).unwrap_or(16);
+        Ok(String::from_utf8_lossy(&bytes[..len]).into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{code_signing::der_entitlements::entitlements_to_der, prelude::*};
+
+    fn name(name: &str) -> [u8; 16] {
+        let mut bytes = [0; 16];
+        bytes[..name.len()].copy_from_slice(name.as_bytes());
+        bytes
+    }
+
+    fn blob(magic: u32, data: &[u8]) -> Vec<u8> {
+        let mut blob = magic.to_be_bytes().to_vec();
+        blob.extend((8 + data.len() as u32).to_be_bytes());
+        blob.extend_from_slice(data);
+        blob
+    }
+
+    /// Builds an arm64 executable with an `__info_plist` section and a code signature
+    /// holding both entitlements slots.
+    fn executable(info_plist: &[u8], entitlements: &Entitlements) -> Vec<u8> {
+        let mut xml = Vec::new();
+        plist::to_writer_xml(&mut xml, entitlements).unwrap();
+        let slots = [
+            blob(CSMAGIC_EMBEDDED_ENTITLEMENTS, &xml),
+            blob(
+                CSMAGIC_EMBEDDED_DER_ENTITLEMENTS,
+                &entitlements_to_der(entitlements).unwrap(),
+            ),
+        ];
+        let mut signature = Vec::new();
+        signature.extend(CSMAGIC_EMBEDDED_SIGNATURE.to_be_bytes());
+        let header_size = 12 + 8 * slots.len();
+        let total = header_size + slots.iter().map(Vec::len).sum::<usize>();
+        signature.extend((total as u32).to_be_bytes());
+        signature.extend((slots.len() as u32).to_be_bytes());
+        let mut offset = header_size;
+        for (slot, blob) in [CSSLOT_ENTITLEMENTS, CSSLOT_DER_ENTITLEMENTS]
+            .iter()
+            .zip(&slots)
+        {
+            signature.extend(slot.to_be_bytes());
+            signature.extend((offset as u32).to_be_bytes());
+            offset += blob.len();
+        }
+        signature.extend(slots.concat());
+
+        let commands_size = 72 + 80 + 16 + 24;
+        let plist_offset = 32 + commands_size;
+        let signature_offset = plist_offset + info_plist.len();
+        let mut file = Vec::new();
+        for value in [
+            MH_MAGIC_64,
+            CPU_TYPE_ARM64,
+            0,
+            2,
+            3,
+            commands_size as u32,
+            0,
+            0,
+        ] {
+            file.extend(value.to_le_bytes());
+        }
+        file.extend(LC_SEGMENT_64.to_le_bytes());
+        file.extend(152u32.to_le_bytes());
+        file.extend(name("__TEXT"));
+        file.extend([0u8; 32]);
+        file.extend([0u8; 8]);
+        file.extend(1u32.to_le_bytes());
+        file.extend(0u32.to_le_bytes());
+        file.extend(name("__info_plist"));
+        file.extend(name("__TEXT"));
+        file.extend(0u64.to_le_bytes());
+        file.extend((info_plist.len() as u64).to_le_bytes());
+        file.extend((plist_offset as u32).to_le_bytes());
+        file.extend([0u8; 28]);
+        for value in [
+            LC_CODE_SIGNATURE,
+            16,
+            signature_offset as u32,
+            signature.len() as u32,
+        ] {
+            file.extend(value.to_le_bytes());
+        }
+        for value in [LC_BUILD_VERSION, 24, 2, 0x000f_0000, 0x0011_0200, 0] {
+            file.extend(value.to_le_bytes());
+        }
+        file.extend_from_slice(info_plist);
+        file.extend(signature);
+        file
+    }
+
+    fn universal(slices: &[Vec<u8>]) -> Vec<u8> {
+        let mut file = FAT_MAGIC.to_be_bytes().to_vec();
+        file.extend((slices.len() as u32).to_be_bytes());
+        let mut offset = 0x4000;
+        for _ in slices {
+            for value in [CPU_TYPE_ARM64, 0, offset, slices[0].len() as u32, 14] {
+                file.extend(value.to_be_bytes());
+            }
+            offset += 0x4000;
+        }
+        for slice in slices {
+            file.resize(file.len().next_multiple_of(0x4000), 0);
+            file.extend(slice);
+        }
+        file
+    }
+
+    #[test]
+    fn reads_entitlements_and_info_plist() {
+        let info_plist = InfoPlist {
+            identification: Identification {
+                bundle_identifier: "com.example.daemon".to_owned(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut plist_data = Vec::new();
+        plist::to_writer_xml(&mut plist_data, &info_plist).unwrap();
+        let entitlements = Entitlements {
+            code_signing: CodeSigning {
+                application_identifier: Some("ABCDE12345.com.example.daemon".to_owned()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let thin = executable(&plist_data, &entitlements);
+        let file = universal(&[thin.clone(), thin]);
+        let slices = MachO::parse(&file).unwrap();
+        assert_eq!(slices.len(), 2);
+        let macho = &slices[1];
+        assert_eq!(macho.architecture(), Some("arm64"));
+        assert_eq!(macho.info_plist().unwrap(), Some(info_plist));
+        assert_eq!(
+            macho.xml_entitlements().unwrap().as_ref(),
+            Some(&entitlements)
+        );
+        assert_eq!(macho.der_entitlements().unwrap(), Some(entitlements));
+        assert_eq!(
+            macho.build_versions,
+            [BuildVersion {
+                platform: BuildVersion::PLATFORM_IOS,
+                minimum_os: (15, 0, 0),
+                sdk: (17, 2, 0),
+            }]
+        );
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(matches!(
+            MachO::parse(b"\x7fELF\x02\x01\x01\x00"),
+            Err(Error::InvalidMachO(_))
+        ));
+        assert!(matches!(
+            MachO::parse(&[0xca, 0xfe, 0xba, 0xbe, 0x7f, 0xff, 0xff, 0xff]),
+            Err(Error::InvalidMachO(_))
+        ));
+    }
+}
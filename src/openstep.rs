@@ -0,0 +1,793 @@
+//! # OpenStep.
+//!
+//! Reader and writer of the old-style ASCII property list format, also known as the
+//! OpenStep or NeXTSTEP format, used by `project.pbxproj`, `defaults export` and legacy
+//! property lists.
+//!
+//! The format only has strings, data, arrays and dictionaries. When deserializing a
+//! type like `InfoPlist` or `Entitlements`, strings like `YES`, `42` or `1.5` become
+//! the booleans, integers and reals the type expects. The typed values of the GNUstep
+//! extension, like `<*BY>` or `<*I42>`, are read as well, and can be written with
+//! [`WriteOptions::gnustep`](crate::openstep::WriteOptions::gnustep).
+//!
+//! Official documentation: <https://developer.apple.com/library/archive/documentation/Cocoa/Conceptual/PropertyLists/OldStylePlists/OldStylePLists.html>
+
+use crate::error::{Error, Result};
+use plist::{Date, Dictionary, Value};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{fmt::Write, path::Path};
+
+/// The largest number of strings, grouped by their path, whose type is guessed
+/// together when deserializing a top-level value. Each guess is a round trip through
+/// the deserialized type, and there are `2^MAX_GUESSES` of them at worst.
+const MAX_GUESSES: usize = 12;
+
+/// The deepest nesting of dictionaries and arrays parsed, so that malformed input
+/// can't overflow the stack.
+const MAX_DEPTH: usize = 128;
+
+/// Options of the ASCII property list writer.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WriteOptions {
+    /// Writes booleans, integers, reals and dates as GNUstep typed values, like
+    /// `<*BY>`, instead of strings.
+    pub gnustep: bool,
+}
+
+/// Parses an old-style ASCII property list into an untyped value.
+pub fn value_from_str(source: &str) -> Result<Value> {
+    let mut parser = Parser {
+        source: source.as_bytes(),
+        position: 0,
+        depth: 0,
+    };
+    let value = parser.parse_value()?;
+    parser.skip_whitespace()?;
+    if parser.position < parser.source.len() {
+        return Err(parser.error("unexpected data after the root value"));
+    }
+    Ok(value)
+}
+
+/// Deserializes an old-style ASCII property list, converting strings to the types `T`
+/// expects.
+pub fn from_str<T: Serialize + DeserializeOwned + Default>(source: &str) -> Result<T> {
+    let value = coerce::<T>(value_from_str(source)?)?;
+    Ok(plist::from_value(&value)?)
+}
+
+/// Reads an old-style ASCII property list file, converting strings to the types `T`
+/// expects.
+pub fn from_file<T: Serialize + DeserializeOwned + Default>(path: impl AsRef<Path>) -> Result<T> {
+    from_str(&std::fs::read_to_string(path)?)
+}
+
+/// Reads a property list file in the XML, binary or old-style ASCII format, skipping
+/// a UTF-8 byte order mark.
+pub(crate) fn read_file<T: Serialize + DeserializeOwned + Default>(
+    path: impl AsRef<Path>,
+) -> Result<T> {
+    let data = std::fs::read(path)?;
+    let data = data.strip_prefix("\u{feff}".as_bytes()).unwrap_or(&data);
+    let start = data.trim_ascii_start();
+    let is_xml = [&b"<?xml"[..], b"<!DOCTYPE", b"<plist"]
+        .iter()
+        .any(|prefix| start.starts_with(prefix));
+    match std::str::from_utf8(data) {
+        Ok(source) if !is_xml && !data.starts_with(b"bplist") => from_str(source),
+        _ => Ok(plist::from_bytes(data)?),
+    }
+}
+
+/// Serializes a value as an old-style ASCII property list.
+pub fn to_string<T: Serialize>(value: &T) -> Result<String> {
+    to_string_with_options(value, &WriteOptions::default())
+}
+
+/// Serializes a value as an old-style ASCII property list, with options.
+pub fn to_string_with_options<T: Serialize>(value: &T, options: &WriteOptions) -> Result<String> {
+    let mut output = String::new();
+    write_value(&mut output, &plist::to_value(value)?, options, 0)?;
+    output.push('\n');
+    Ok(output)
+}
+
+/// Writes a value as an old-style ASCII property list file.
+pub fn to_file<T: Serialize>(path: impl AsRef<Path>, value: &T) -> Result<()> {
+    Ok(std::fs::write(path, to_string(value)?)?)
+}
+
+struct Parser<'a> {
+    source: &'a [u8],
+    position: usize,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, reason: &str) -> Error {
+        let line = self.source[..self.position.min(self.source.len())]
+            .iter()
+            .filter(|&&byte| byte == b'\n')
+            .count();
+        Error::InvalidAsciiPlist(format!("line {}: {}", line + 1, reason))
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.source.get(self.position).copied()
+    }
+
+    /// Skips whitespace and `//` and `/* */` comments.
+    fn skip_whitespace(&mut self) -> Result<()> {
+        loop {
+            match (self.peek(), self.source.get(self.position + 1)) {
+                (Some(byte), _) if byte.is_ascii_whitespace() => self.position += 1,
+                (Some(b'/'), Some(b'/')) => {
+                    while self.peek().is_some_and(|byte| byte != b'\n') {
+                        self.position += 1;
+                    }
+                }
+                (Some(b'/'), Some(b'*')) => {
+                    let end = self.source[self.position + 2..]
+                        .windows(2)
+                        .position(|window| window == b"*/")
+                        .ok_or_else(|| self.error("unterminated comment"))?;
+                    self.position += end + 4;
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<()> {
+        self.skip_whitespace()?;
+        if self.peek() != Some(byte) {
+            return Err(self.error(&format!("expected `{}`", byte as char)));
+        }
+        self.position += 1;
+        Ok(())
+    }
+
+    fn parse_value(&mut self) -> Result<Value> {
+        self.skip_whitespace()?;
+        match self.peek() {
+            Some(byte @ (b'{' | b'(')) => {
+                if self.depth == MAX_DEPTH {
+                    return Err(self.error("values nested too deeply"));
+                }
+                self.depth += 1;
+                let value = match byte {
+                    b'{' => self.parse_dictionary(),
+                    _ => self.parse_array(),
+                };
+                self.depth -= 1;
+                value
+            }
+            Some(b'<') => self.parse_data(),
+            Some(b'"') | Some(b'\'') => self.parse_quoted().map(Value::String),
+            Some(byte) if is_unquoted(byte) => Ok(Value::String(self.parse_unquoted())),
+            Some(_) => Err(self.error("unexpected character")),
+            None => Err(self.error("unexpected end of data")),
+        }
+    }
+
+    fn parse_dictionary(&mut self) -> Result<Value> {
+        self.position += 1;
+        let mut dictionary = Dictionary::new();
+        loop {
+            self.skip_whitespace()?;
+            if self.peek() == Some(b'}') {
+                self.position += 1;
+                return Ok(Value::Dictionary(dictionary));
+            }
+            let key = match self.parse_value()? {
+                Value::String(key) => key,
+                _ => return Err(self.error("dictionary keys must be strings")),
+            };
+            self.expect(b'=')?;
+            let value = self.parse_value()?;
+            self.expect(b';')?;
+            dictionary.insert(key, value);
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<Value> {
+        self.position += 1;
+        let mut array = Vec::new();
+        loop {
+            self.skip_whitespace()?;
+            if self.peek() == Some(b')') {
+                self.position += 1;
+                return Ok(Value::Array(array));
+            }
+            array.push(self.parse_value()?);
+            self.skip_whitespace()?;
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                Some(b')') => {}
+                _ => return Err(self.error("expected `,` or `)`")),
+            }
+        }
+    }
+
+    fn parse_data(&mut self) -> Result<Value> {
+        self.position += 1;
+        if self.peek() == Some(b'*') {
+            return self.parse_typed();
+        }
+        let mut data = Vec::new();
+        let mut high = None;
+        loop {
+            let byte = self.peek().ok_or_else(|| self.error("unterminated data"))?;
+            self.position += 1;
+            let nibble = match byte {
+                b'>' if high.is_none() => return Ok(Value::Data(data)),
+                byte if byte.is_ascii_whitespace() => continue,
+                byte if byte.is_ascii_hexdigit() => (byte as char).to_digit(16).unwrap() as u8,
+                _ => return Err(self.error("invalid data")),
+            };
+            match high.take() {
+                Some(high) => data.push(high << 4 | nibble),
+                None => high = Some(nibble),
+            }
+        }
+    }
+
+    /// Parses a GNUstep typed value, like `<*I42>`, after its `<`.
+    fn parse_typed(&mut self) -> Result<Value> {
+        let start = self.position + 2;
+        let end = self.source[start.min(self.source.len())..]
+            .iter()
+            .position(|&byte| byte == b'>')
+            .map(|end| start + end)
+            .ok_or_else(|| self.error("unterminated typed value"))?;
+        let kind = self.source[self.position + 1];
+        let content = std::str::from_utf8(&self.source[start..end])
+            .map_err(|_| self.error("invalid UTF-8 in typed value"))?
+            .trim();
+        let value = match kind {
+            b'B' => match content {
+                "Y" => Some(Value::Boolean(true)),
+                "N" => Some(Value::Boolean(false)),
+                _ => None,
+            },
+            b'I' => integer(content),
+            b'R' => content.parse::<f64>().ok().map(Value::Real),
+            b'D' => content
+                .strip_suffix(" +0000")
+                .map(|date| format!("{}Z", date.replacen(' ', "T", 1)))
+                .and_then(|date| Date::from_xml_format(&date).ok())
+                .map(Value::Date),
+            _ => None,
+        }
+        .ok_or_else(|| self.error("invalid typed value"))?;
+        self.position = end + 1;
+        Ok(value)
+    }
+
+    fn parse_unquoted(&mut self) -> String {
+        let start = self.position;
+        while self.peek().is_some_and(is_unquoted) {
+            self.position += 1;
+        }
+        String::from_utf8_lossy(&self.source[start..self.position]).into_owned()
+    }
+
+    fn parse_quoted(&mut self) -> Result<String> {
+        let quote = self.source[self.position];
+        self.position += 1;
+        let mut bytes = Vec::new();
+        loop {
+            let byte = self
+                .peek()
+                .ok_or_else(|| self.error("unterminated string"))?;
+            self.position += 1;
+            match byte {
+                byte if byte == quote => break,
+                b'\\' => self.parse_escape(&mut bytes)?,
+                byte => bytes.push(byte),
+            }
+        }
+        String::from_utf8(bytes).map_err(|_| self.error("invalid UTF-8 in string"))
+    }
+
+    fn parse_escape(&mut self, bytes: &mut Vec<u8>) -> Result<()> {
+        let byte = self
+            .peek()
+            .ok_or_else(|| self.error("unterminated string"))?;
+        self.position += 1;
+        let escaped = match byte {
+            b'a' => 0x07,
+            b'b' => 0x08,
+            b'f' => 0x0c,
+            b'n' => b'\n',
+            b'r' => b'\r',
+            b't' => b'\t',
+            b'v' => 0x0b,
+            b'U' | b'u' => {
+                let digits = self
+                    .source
+                    .get(self.position..self.position + 4)
+                    .and_then(|digits| std::str::from_utf8(digits).ok())
+                    .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+                    .ok_or_else(|| self.error("invalid unicode escape"))?;
+                self.position += 4;
+                let c = char::from_u32(digits).unwrap_or(char::REPLACEMENT_CHARACTER);
+                bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                return Ok(());
+            }
+            b'0'..=b'7' => {
+                let mut value = u32::from(byte - b'0');
+                for _ in 0..2 {
+                    match self.peek() {
+                        Some(digit @ b'0'..=b'7') => {
+                            value = value * 8 + u32::from(digit - b'0');
+                            self.position += 1;
+                        }
+                        _ => break,
+                    }
+                }
+                // Octal escapes are NeXTSTEP encoded, which matches Latin-1 for the
+                // characters that appear in practice.
+                let c = char::from_u32(value).unwrap_or(char::REPLACEMENT_CHARACTER);
+                bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                return Ok(());
+            }
+            byte => byte,
+        };
+        bytes.push(escaped);
+        Ok(())
+    }
+}
+
+/// Returns whether `byte` can appear in an unquoted string.
+fn is_unquoted(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || matches!(byte, b'_' | b'$' | b'+' | b'/' | b':' | b'.' | b'-')
+}
+
+/// Returns the integer of a string like `42` or `-1`.
+fn integer(value: &str) -> Option<Value> {
+    match value.parse::<i64>() {
+        Ok(integer) => Some(integer.into()),
+        Err(_) => value.parse::<u64>().ok().map(Value::from),
+    }
+}
+
+/// Returns the boolean, integer or real a string of an ASCII property list can stand
+/// for.
+fn typed(value: &str) -> Option<Value> {
+    match value {
+        "YES" | "yes" | "true" => return Some(true.into()),
+        "NO" | "no" | "false" => return Some(false.into()),
+        _ => {}
+    }
+    let numeric = value.starts_with(|c: char| c.is_ascii_digit() || c == '-')
+        && value
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E'));
+    if !numeric {
+        return None;
+    }
+    integer(value).or_else(|| value.parse::<f64>().ok().map(Value::Real))
+}
+
+/// Converts the strings of a parsed ASCII property list to the types `T` expects.
+///
+/// Every top-level key is tried on its own, on top of `T::default()` so that required
+/// keys are present. The strings that could be typed are grouped by their path, with
+/// array indices ignored, and the groups are typed or kept as strings until the value
+/// survives a round trip through `T`. Strings are kept when `T` doesn't model the key.
+fn coerce<T: Serialize + DeserializeOwned + Default>(value: Value) -> Result<Value> {
+    match (value, plist::to_value(&T::default())?) {
+        (Value::Dictionary(dictionary), Value::Dictionary(base)) => {
+            let mut coerced = Dictionary::new();
+            for (key, value) in dictionary {
+                let value = coerce_value(&key, value, |candidate| {
+                    let mut document = base.clone();
+                    document.insert(key.clone(), candidate.clone());
+                    let round_trip = round_trip::<T>(&Value::Dictionary(document))?;
+                    // A key that `T` doesn't model is dropped by the round trip.
+                    Some(
+                        round_trip
+                            .as_dictionary()
+                            .and_then(|dictionary| dictionary.get(&key))
+                            .is_none_or(|value| value == candidate),
+                    )
+                })?;
+                coerced.insert(key, value);
+            }
+            Ok(Value::Dictionary(coerced))
+        }
+        (value, _) => coerce_value("the root value", value, |candidate| {
+            Some(round_trip::<T>(candidate)? == *candidate)
+        }),
+    }
+}
+
+fn round_trip<T: Serialize + DeserializeOwned>(value: &Value) -> Option<Value> {
+    let value: T = plist::from_value(value).ok()?;
+    plist::to_value(&value).ok()
+}
+
+/// Returns the first typing of the strings of `value` that `test` accepts exactly, or
+/// else the first that it can deserialize, or else `value` unchanged.
+///
+/// Keeping every string and typing every string are tried first, as they cover most
+/// values. Guessing is skipped past [`MAX_GUESSES`] groups of strings, and fails when
+/// neither can be deserialized.
+fn coerce_value(name: &str, value: Value, test: impl Fn(&Value) -> Option<bool>) -> Result<Value> {
+    let mut groups = Vec::new();
+    collect_groups(&value, &mut String::new(), &mut groups);
+    if groups.is_empty() {
+        return Ok(value);
+    }
+    let with_typed = |typed: &[&str]| {
+        let mut candidate = value.clone();
+        apply_groups(&mut candidate, &mut String::new(), typed);
+        candidate
+    };
+    let all: Vec<&str> = groups.iter().map(String::as_str).collect();
+    let mut fallback = None;
+    for candidate in [value.clone(), with_typed(&all)] {
+        match test(&candidate) {
+            Some(true) => return Ok(candidate),
+            Some(false) if fallback.is_none() => fallback = Some(candidate),
+            _ => {}
+        }
+    }
+    if groups.len() > MAX_GUESSES {
+        return fallback.ok_or_else(|| {
+            Error::InvalidAsciiPlist(format!(
+                "can't convert {}, it has more than {} kinds of values",
+                name, MAX_GUESSES
+            ))
+        });
+    }
+    for mask in 1..(1u32 << groups.len()) - 1 {
+        let typed: Vec<&str> = groups
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| mask & 1 << index != 0)
+            .map(|(_, path)| path.as_str())
+            .collect();
+        let candidate = with_typed(&typed);
+        match test(&candidate) {
+            Some(true) => return Ok(candidate),
+            Some(false) if fallback.is_none() => fallback = Some(candidate),
+            _ => {}
+        }
+    }
+    Ok(fallback.unwrap_or(value))
+}
+
+/// Collects the paths of the strings that could be typed, like `/CFBundleURLTypes/[]`.
+fn collect_groups(value: &Value, path: &mut String, groups: &mut Vec<String>) {
+    match value {
+        Value::String(string) if typed(string).is_some() && !groups.contains(path) => {
+            groups.push(path.clone());
+        }
+        Value::Array(array) => {
+            let len = path.len();
+            path.push_str("/[]");
+            for value in array {
+                collect_groups(value, path, groups);
+            }
+            path.truncate(len);
+        }
+        Value::Dictionary(dictionary) => {
+            for (key, value) in dictionary {
+                let len = path.len();
+                path.push('/');
+                path.push_str(key);
+                collect_groups(value, path, groups);
+                path.truncate(len);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn apply_groups(value: &mut Value, path: &mut String, typed_groups: &[&str]) {
+    match value {
+        Value::String(string) if typed_groups.contains(&path.as_str()) => {
+            if let Some(typed) = typed(string) {
+                *value = typed;
+            }
+        }
+        Value::Array(array) => {
+            let len = path.len();
+            path.push_str("/[]");
+            for value in array {
+                apply_groups(value, path, typed_groups);
+            }
+            path.truncate(len);
+        }
+        Value::Dictionary(dictionary) => {
+            for (key, value) in dictionary.iter_mut() {
+                let len = path.len();
+                path.push('/');
+                path.push_str(key);
+                apply_groups(value, path, typed_groups);
+                path.truncate(len);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn write_value(
+    output: &mut String,
+    value: &Value,
+    options: &WriteOptions,
+    depth: usize,
+) -> Result<()> {
+    let indent = |output: &mut String, depth: usize| {
+        for _ in 0..depth {
+            output.push('\t');
+        }
+    };
+    match value {
+        Value::Dictionary(dictionary) if dictionary.is_empty() => output.push_str("{}"),
+        Value::Dictionary(dictionary) => {
+            output.push_str("{\n");
+            for (key, value) in dictionary {
+                indent(output, depth + 1);
+                write_string(output, key);
+                output.push_str(" = ");
+                write_value(output, value, options, depth + 1)?;
+                output.push_str(";\n");
+            }
+            indent(output, depth);
+            output.push('}');
+        }
+        Value::Array(array) if array.is_empty() => output.push_str("()"),
+        Value::Array(array) => {
+            output.push_str("(\n");
+            for value in array {
+                indent(output, depth + 1);
+                write_value(output, value, options, depth + 1)?;
+                output.push_str(",\n");
+            }
+            indent(output, depth);
+            output.push(')');
+        }
+        Value::Data(data) => {
+            output.push('<');
+            for (index, byte) in data.iter().enumerate() {
+                if index > 0 && index % 4 == 0 {
+                    output.push(' ');
+                }
+                let _ = write!(output, "{:02x}", byte);
+            }
+            output.push('>');
+        }
+        Value::String(string) => write_string(output, string),
+        Value::Boolean(value) => match (options.gnustep, value) {
+            (true, true) => output.push_str("<*BY>"),
+            (true, false) => output.push_str("<*BN>"),
+            (false, true) => output.push_str("YES"),
+            (false, false) => output.push_str("NO"),
+        },
+        Value::Integer(integer) if options.gnustep => {
+            let _ = write!(output, "<*I{}>", integer);
+        }
+        Value::Integer(integer) => write_string(output, &integer.to_string()),
+        Value::Real(real) if options.gnustep => {
+            let _ = write!(output, "<*R{}>", real);
+        }
+        Value::Real(real) => write_string(output, &real.to_string()),
+        Value::Date(date) => {
+            let date = date.to_xml_format();
+            let date = date.replacen('T', " ", 1).replace('Z', " +0000");
+            if options.gnustep {
+                let _ = write!(output, "<*D{}>", date);
+            } else {
+                write_string(output, &date);
+            }
+        }
+        Value::Uid(uid) => write_string(output, &uid.get().to_string()),
+        _ => {
+            return Err(Error::InvalidAsciiPlist(
+                "unsupported property list value".to_owned(),
+            ))
+        }
+    }
+    Ok(())
+}
+
+/// Writes a string, quoted and escaped unless it only has unquoted characters.
+fn write_string(output: &mut String, string: &str) {
+    // Slashes are allowed unquoted, but `//` and `/*` would start a comment.
+    let is_comment = string.contains("//") || string.contains("/*");
+    if !string.is_empty() && string.bytes().all(is_unquoted) && !is_comment {
+        output.push_str(string);
+        return;
+    }
+    output.push('"');
+    for c in string.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(output, "\\U{:04x}", c as u32);
+            }
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn parses_ascii_plist() {
+        let value = value_from_str(
+            r#"// !$*UTF8*$!
+            {
+                /* Begin section */
+                name = "Hello \"World\"\n\U00e9";
+                path = Sources/main.swift; // trailing
+                list = (a, "b c", );
+                data = <0fbd 77>;
+                empty = {};
+            }"#,
+        )
+        .unwrap();
+        let dictionary = value.as_dictionary().unwrap();
+        assert_eq!(
+            dictionary.get("name"),
+            Some(&Value::String("Hello \"World\"\né".to_owned()))
+        );
+        assert_eq!(
+            dictionary.get("path"),
+            Some(&Value::String("Sources/main.swift".to_owned()))
+        );
+        assert_eq!(
+            dictionary.get("list"),
+            Some(&Value::Array(vec!["a".into(), "b c".into()]))
+        );
+        assert_eq!(
+            dictionary.get("data"),
+            Some(&Value::Data(vec![0x0f, 0xbd, 0x77]))
+        );
+        assert_eq!(
+            dictionary.get("empty"),
+            Some(&Value::Dictionary(Dictionary::new()))
+        );
+        for source in [
+            "{ a = b }",
+            "(a b)",
+            "<0f",
+            "\"open",
+            "{ a = b; } c",
+            "/* open",
+            "<*BX>",
+        ] {
+            assert!(value_from_str(source).is_err(), "{}", source);
+        }
+    }
+
+    #[test]
+    fn deserializes_typed_values() {
+        let info_plist: InfoPlist = from_str(
+            r#"{
+                CFBundleIdentifier = com.example.app;
+                CFBundleVersion = 1;
+                CFBundleShortVersionString = 1.0;
+                LSRequiresIPhoneOS = YES;
+                UIDeviceFamily = (1, 2);
+                UIRequiresFullScreen = <*BN>;
+                CFBundleURLTypes = (
+                    { CFBundleURLName = example; CFBundleURLSchemes = (example, 42); },
+                );
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            info_plist.identification.bundle_identifier,
+            "com.example.app"
+        );
+        assert_eq!(
+            info_plist.bundle_version.bundle_version.as_deref(),
+            Some("1")
+        );
+        assert_eq!(
+            info_plist
+                .bundle_version
+                .bundle_short_version_string
+                .as_deref(),
+            Some("1.0")
+        );
+        assert_eq!(
+            info_plist.operating_system_version.requires_iphone_os,
+            Some(true)
+        );
+        assert_eq!(info_plist.styling.requires_full_screen, Some(false));
+        assert_eq!(
+            info_plist.background_execution.ui_device_family,
+            Some(vec![1, 2])
+        );
+        let url_types = info_plist.url_schemes.bundle_url_types.as_ref().unwrap();
+        assert_eq!(
+            url_types[0].bundle_url_schemes,
+            Some(vec!["example".to_owned(), "42".to_owned()])
+        );
+
+        let written = to_string(&info_plist).unwrap();
+        assert!(
+            written.contains("\tLSRequiresIPhoneOS = YES;\n"),
+            "{}",
+            written
+        );
+        assert_eq!(from_str::<InfoPlist>(&written).unwrap(), info_plist);
+        let gnustep = WriteOptions { gnustep: true };
+        let written = to_string_with_options(&info_plist, &gnustep).unwrap();
+        assert!(
+            written.contains("LSRequiresIPhoneOS = <*BY>;"),
+            "{}",
+            written
+        );
+        assert_eq!(from_str::<InfoPlist>(&written).unwrap(), info_plist);
+    }
+
+    #[test]
+    fn reads_files_with_byte_order_mark() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Info.plist");
+        let info_plist = InfoPlist {
+            identification: Identification {
+                bundle_identifier: "com.example.app".to_owned(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut xml = "\u{feff}".as_bytes().to_vec();
+        plist::to_writer_xml(&mut xml, &info_plist).unwrap();
+        std::fs::write(&path, xml).unwrap();
+        assert_eq!(read_file::<InfoPlist>(&path).unwrap(), info_plist);
+        std::fs::write(&path, "\u{feff}{ CFBundleIdentifier = com.example.app; }").unwrap();
+        assert_eq!(read_file::<InfoPlist>(&path).unwrap(), info_plist);
+    }
+
+    #[test]
+    fn quotes_comment_markers() {
+        let mut dictionary = Dictionary::new();
+        for (key, value) in [("a", "//x"), ("b", "x/*y"), ("c", "x/y")] {
+            dictionary.insert(key.to_owned(), value.into());
+        }
+        let value = Value::Dictionary(dictionary);
+        let written = to_string(&value).unwrap();
+        assert!(written.contains("a = \"//x\";"), "{}", written);
+        assert!(written.contains("c = x/y;"), "{}", written);
+        assert_eq!(value_from_str(&written).unwrap(), value);
+    }
+
+    #[test]
+    fn rejects_deeply_nested_values() {
+        assert!(matches!(
+            value_from_str(&"(".repeat(200_000)),
+            Err(Error::InvalidAsciiPlist(_))
+        ));
+        let nested = format!("{}{}", "(".repeat(MAX_DEPTH), ")".repeat(MAX_DEPTH));
+        assert!(value_from_str(&nested).is_ok());
+    }
+
+    #[test]
+    fn skips_guesses_for_values_with_many_groups() {
+        let values: Vec<String> = (0..=MAX_GUESSES)
+            .map(|index| format!("k{} = {};", index, index))
+            .collect();
+        let source = format!(
+            "{{ CFBundleIdentifier = a; CFBundleURLTypes = ({{ CFBundleURLName = 1; {} }}); }}",
+            values.join(" ")
+        );
+        let info_plist = from_str::<InfoPlist>(&source).unwrap();
+        let url_types = info_plist.url_schemes.bundle_url_types.unwrap();
+        assert_eq!(url_types[0].bundle_url_name, "1");
+    }
+}
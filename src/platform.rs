@@ -0,0 +1,311 @@
+use crate::{
+    error::{Error, Result},
+    info_plist::InfoPlist,
+};
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+/// An Apple operating system that bundles are built for.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Platform {
+    #[serde(rename = "iOS")]
+    Ios,
+    #[serde(rename = "macOS")]
+    MacOs,
+    #[serde(rename = "tvOS")]
+    TvOs,
+    #[serde(rename = "watchOS")]
+    WatchOs,
+    #[serde(rename = "visionOS")]
+    VisionOs,
+}
+
+impl fmt::Display for Platform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&serde_plain::to_string(self).unwrap())
+    }
+}
+
+/// The environment of a Rust target, given by the last component of the triple.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TargetEnvironment {
+    /// A device of the platform.
+    Device,
+    /// The simulator of the platform, `-sim`, or an Intel target of a platform that
+    /// only runs on Apple silicon.
+    Simulator,
+    /// An iOS app running on macOS, `-macabi`.
+    MacCatalyst,
+}
+
+/// An Apple Rust target, like `aarch64-apple-ios-sim` or `x86_64-apple-darwin`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Target {
+    /// The architecture, like `aarch64` or `x86_64`.
+    pub architecture: String,
+    /// The operating system of the triple. Mac Catalyst targets are `iOS`.
+    pub os: Platform,
+    /// The environment of the triple.
+    pub environment: TargetEnvironment,
+}
+
+impl Target {
+    /// Returns the platform that the target runs on, which is macOS for Mac Catalyst.
+    pub fn platform(&self) -> Platform {
+        match self.environment {
+            TargetEnvironment::MacCatalyst => Platform::MacOs,
+            _ => self.os,
+        }
+    }
+
+    /// Returns the SDK platform name written to `DTPlatformName`, like
+    /// `iphonesimulator`.
+    pub fn platform_name(&self) -> &'static str {
+        use TargetEnvironment::*;
+        match (self.os, self.environment) {
+            (Platform::MacOs, _) | (_, MacCatalyst) => "macosx",
+            (Platform::Ios, Device) => "iphoneos",
+            (Platform::Ios, Simulator) => "iphonesimulator",
+            (Platform::TvOs, Device) => "appletvos",
+            (Platform::TvOs, Simulator) => "appletvsimulator",
+            (Platform::WatchOs, Device) => "watchos",
+            (Platform::WatchOs, Simulator) => "watchsimulator",
+            (Platform::VisionOs, Device) => "xros",
+            (Platform::VisionOs, Simulator) => "xrsimulator",
+        }
+    }
+
+    /// Returns the platform written to `CFBundleSupportedPlatforms`, like
+    /// `iPhoneSimulator`.
+    pub fn supported_platform(&self) -> &'static str {
+        use TargetEnvironment::*;
+        match (self.os, self.environment) {
+            (Platform::MacOs, _) | (_, MacCatalyst) => "MacOSX",
+            (Platform::Ios, Device) => "iPhoneOS",
+            (Platform::Ios, Simulator) => "iPhoneSimulator",
+            (Platform::TvOs, Device) => "AppleTVOS",
+            (Platform::TvOs, Simulator) => "AppleTVSimulator",
+            (Platform::WatchOs, Device) => "WatchOS",
+            (Platform::WatchOs, Simulator) => "WatchSimulator",
+            (Platform::VisionOs, Device) => "XROS",
+            (Platform::VisionOs, Simulator) => "XRSimulator",
+        }
+    }
+
+    /// Returns the `UIDeviceFamily` values of apps built for the target: 1 for iPhone,
+    /// 2 for iPad, 3 for Apple TV, 4 for Apple Watch, 6 for Mac and 7 for Apple Vision.
+    pub fn device_family(&self) -> &'static [u8] {
+        match (self.os, self.environment) {
+            (_, TargetEnvironment::MacCatalyst) => &[2, 6],
+            (Platform::Ios, _) => &[1, 2],
+            (Platform::TvOs, _) => &[3],
+            (Platform::WatchOs, _) => &[4],
+            (Platform::VisionOs, _) => &[7],
+            (Platform::MacOs, _) => &[],
+        }
+    }
+
+    /// Returns the information property list key holding the deployment target:
+    /// `LSMinimumSystemVersion` on macOS and `MinimumOSVersion` on other platforms.
+    pub fn minimum_version_key(&self) -> &'static str {
+        match self.platform() {
+            Platform::MacOs => "LSMinimumSystemVersion",
+            _ => "MinimumOSVersion",
+        }
+    }
+
+    /// Returns the environment variable that sets the deployment target of the
+    /// target, like `IPHONEOS_DEPLOYMENT_TARGET`.
+    ///
+    /// Mac Catalyst targets are deployed to an iOS version.
+    pub fn deployment_target_env(&self) -> &'static str {
+        match (self.os, self.environment) {
+            (_, TargetEnvironment::MacCatalyst) | (Platform::Ios, _) => {
+                "IPHONEOS_DEPLOYMENT_TARGET"
+            }
+            (Platform::MacOs, _) => "MACOSX_DEPLOYMENT_TARGET",
+            (Platform::TvOs, _) => "TVOS_DEPLOYMENT_TARGET",
+            (Platform::WatchOs, _) => "WATCHOS_DEPLOYMENT_TARGET",
+            (Platform::VisionOs, _) => "XROS_DEPLOYMENT_TARGET",
+        }
+    }
+
+    /// Fills in the platform-dependent keys of `info_plist` that aren't set yet.
+    ///
+    /// The deployment target, if given, is written to the key given by
+    /// [`Target::minimum_version_key`]. The iOS deployment target of a Mac Catalyst
+    /// target isn't written, since `LSMinimumSystemVersion` holds a macOS version.
+    pub fn apply_defaults(&self, info_plist: &mut InfoPlist, deployment_target: Option<&str>) {
+        let os_version = &mut info_plist.operating_system_version;
+        os_version
+            .supported_platforms
+            .get_or_insert_with(|| vec![self.supported_platform().to_owned()]);
+        os_version
+            .platform_name
+            .get_or_insert_with(|| self.platform_name().to_owned());
+        if matches!(self.os, Platform::Ios | Platform::TvOs)
+            && self.environment != TargetEnvironment::MacCatalyst
+        {
+            os_version.requires_iphone_os.get_or_insert(true);
+        }
+        let deployment_target =
+            deployment_target.filter(|_| self.environment != TargetEnvironment::MacCatalyst);
+        if let Some(deployment_target) = deployment_target {
+            let minimum_version = match self.platform() {
+                Platform::MacOs => &mut os_version.minimum_system_version,
+                _ => &mut os_version.minimum_os_version,
+            };
+            minimum_version.get_or_insert_with(|| deployment_target.to_owned());
+        }
+        if !self.device_family().is_empty() {
+            info_plist
+                .background_execution
+                .ui_device_family
+                .get_or_insert_with(|| self.device_family().to_vec());
+        }
+    }
+}
+
+impl FromStr for Target {
+    type Err = Error;
+
+    fn from_str(triple: &str) -> Result<Self> {
+        let invalid = || Error::InvalidTarget(triple.to_owned());
+        let mut components = triple.split('-');
+        let (Some(architecture), Some("apple"), Some(os)) =
+            (components.next(), components.next(), components.next())
+        else {
+            return Err(invalid());
+        };
+        let os = match os {
+            "darwin" | "macos" => Platform::MacOs,
+            "ios" => Platform::Ios,
+            "tvos" => Platform::TvOs,
+            "watchos" => Platform::WatchOs,
+            "visionos" => Platform::VisionOs,
+            _ => return Err(invalid()),
+        };
+        let intel = matches!(architecture, "x86_64" | "i386" | "i686");
+        let environment = match (components.next(), os) {
+            (Some("sim"), Platform::MacOs) => return Err(invalid()),
+            (Some("sim"), _) => TargetEnvironment::Simulator,
+            (Some("macabi"), Platform::Ios) => TargetEnvironment::MacCatalyst,
+            (None, Platform::MacOs) => TargetEnvironment::Device,
+            (None, _) if intel => TargetEnvironment::Simulator,
+            (None, _) => TargetEnvironment::Device,
+            _ => return Err(invalid()),
+        };
+        if components.next().is_some() {
+            return Err(invalid());
+        }
+        Ok(Self {
+            architecture: architecture.to_owned(),
+            os,
+            environment,
+        })
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let os = match self.os {
+            Platform::Ios => "ios",
+            Platform::MacOs => "darwin",
+            Platform::TvOs => "tvos",
+            Platform::WatchOs => "watchos",
+            Platform::VisionOs => "visionos",
+        };
+        write!(f, "{}-apple-{}", self.architecture, os)?;
+        let intel = matches!(self.architecture.as_str(), "x86_64" | "i386" | "i686");
+        match self.environment {
+            // Intel simulator targets predate the `-sim` suffix, except on watchOS.
+            TargetEnvironment::Simulator if !intel || self.os == Platform::WatchOs => {
+                f.write_str("-sim")
+            }
+            TargetEnvironment::MacCatalyst => f.write_str("-macabi"),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_target_triples() {
+        for (triple, platform, platform_name) in [
+            ("aarch64-apple-ios", Platform::Ios, "iphoneos"),
+            ("aarch64-apple-ios-sim", Platform::Ios, "iphonesimulator"),
+            ("x86_64-apple-ios", Platform::Ios, "iphonesimulator"),
+            (
+                "x86_64-apple-watchos-sim",
+                Platform::WatchOs,
+                "watchsimulator",
+            ),
+            ("aarch64-apple-ios-macabi", Platform::MacOs, "macosx"),
+            ("x86_64-apple-darwin", Platform::MacOs, "macosx"),
+            ("aarch64-apple-tvos", Platform::TvOs, "appletvos"),
+            ("arm64_32-apple-watchos", Platform::WatchOs, "watchos"),
+            (
+                "aarch64-apple-visionos-sim",
+                Platform::VisionOs,
+                "xrsimulator",
+            ),
+        ] {
+            let target: Target = triple.parse().unwrap();
+            assert_eq!(target.platform(), platform, "{}", triple);
+            assert_eq!(target.platform_name(), platform_name, "{}", triple);
+            assert_eq!(target.to_string(), triple);
+        }
+        for triple in [
+            "aarch64-unknown-linux-gnu",
+            "aarch64-apple-darwin-sim",
+            "aarch64-apple-tvos-macabi",
+            "aarch64-apple",
+        ] {
+            assert!(triple.parse::<Target>().is_err(), "{}", triple);
+        }
+    }
+
+    #[test]
+    fn applies_platform_defaults() {
+        let mut info_plist = InfoPlist::default();
+        let target: Target = "aarch64-apple-ios-sim".parse().unwrap();
+        target.apply_defaults(&mut info_plist, Some("15.0"));
+        let os_version = &info_plist.operating_system_version;
+        assert_eq!(
+            os_version.supported_platforms.as_deref(),
+            Some(&["iPhoneSimulator".to_owned()][..])
+        );
+        assert_eq!(os_version.platform_name.as_deref(), Some("iphonesimulator"));
+        assert_eq!(os_version.requires_iphone_os, Some(true));
+        assert_eq!(os_version.minimum_os_version.as_deref(), Some("15.0"));
+        assert_eq!(os_version.minimum_system_version, None);
+        assert_eq!(
+            info_plist.background_execution.ui_device_family,
+            Some(vec![1, 2])
+        );
+
+        let mut info_plist = InfoPlist::default();
+        let target: Target = "aarch64-apple-darwin".parse().unwrap();
+        target.apply_defaults(&mut info_plist, Some("11.0"));
+        let os_version = &info_plist.operating_system_version;
+        assert_eq!(os_version.minimum_system_version.as_deref(), Some("11.0"));
+        assert_eq!(os_version.requires_iphone_os, None);
+        assert_eq!(info_plist.background_execution.ui_device_family, None);
+
+        let mut info_plist = InfoPlist::default();
+        let target: Target = "aarch64-apple-ios-macabi".parse().unwrap();
+        assert_eq!(target.deployment_target_env(), "IPHONEOS_DEPLOYMENT_TARGET");
+        target.apply_defaults(&mut info_plist, Some("15.0"));
+        let os_version = &info_plist.operating_system_version;
+        assert_eq!(os_version.minimum_system_version, None);
+        assert_eq!(os_version.minimum_os_version, None);
+        assert_eq!(os_version.requires_iphone_os, None);
+        assert_eq!(
+            info_plist.background_execution.ui_device_family,
+            Some(vec![2, 6])
+        );
+    }
+}
@@ -0,0 +1,348 @@
+//! # Property List Diff.
+//!
+//! Structural differences between two property lists, like the `InfoPlist` or the
+//! `Entitlements` of two releases of an app.
+//!
+//! Changes are reported by key path, with keys and array indices separated by `:` like
+//! in [`PlistDocument`](crate::plist_document::PlistDocument). The arrays of the keys
+//! in [`SET_KEYS`](crate::plist_diff::SET_KEYS), like `UIBackgroundModes`, are compared
+//! as sets, so reordering them isn't a change, but duplicating an element is. Other
+//! arrays, like `CFBundleURLTypes` or `UISupportedInterfaceOrientations`, are ordered
+//! and compared index by index.
+
+use crate::error::Result;
+use plist::Value;
+use serde::Serialize;
+use std::fmt;
+
+/// The keys whose arrays are unordered, like background modes, app groups and
+/// associated domains.
+pub const SET_KEYS: &[&str] = &[
+    "CFBundleURLSchemes",
+    "LSApplicationQueriesSchemes",
+    "UIBackgroundModes",
+    "UIDeviceFamily",
+    "UIRequiredDeviceCapabilities",
+    "com.apple.developer.associated-domains",
+    "com.apple.developer.icloud-container-identifiers",
+    "com.apple.developer.icloud-services",
+    "com.apple.developer.ubiquity-container-identifiers",
+    "com.apple.security.application-groups",
+    "keychain-access-groups",
+];
+
+/// The changes between two property lists.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PlistDiff {
+    /// The changes, in the order of the keys of the old property list, followed by
+    /// the keys only the new one has.
+    pub changes: Vec<Change>,
+}
+
+/// A change of a value at a key path.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Change {
+    /// The key path of the value, or of the array for an element added to or removed
+    /// from a set.
+    pub path: String,
+    /// Whether the value was added, removed or changed.
+    pub kind: ChangeKind,
+    /// The value before the change, unless it was added.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old: Option<Value>,
+    /// The value after the change, unless it was removed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new: Option<Value>,
+}
+
+/// The kind of a change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    /// The value is only in the new property list, or an element was added to a set.
+    Added,
+    /// The value is only in the old property list, or an element was removed from a
+    /// set.
+    Removed,
+    /// The value differs between the property lists.
+    Changed,
+}
+
+impl PlistDiff {
+    /// Compares two serializable values, like two `InfoPlist`s or two `Entitlements`.
+    pub fn new<T: Serialize>(old: &T, new: &T) -> Result<Self> {
+        Ok(Self::between(
+            &plist::to_value(old)?,
+            &plist::to_value(new)?,
+        ))
+    }
+
+    /// Compares two property list values.
+    pub fn between(old: &Value, new: &Value) -> Self {
+        let mut diff = Self::default();
+        diff.compare(&mut Vec::new(), old, new);
+        diff
+    }
+
+    /// Returns whether the property lists are the same.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Returns the changes as pretty-printed JSON, for CI annotations.
+    #[cfg(feature = "serde_json")]
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(&self.changes)?)
+    }
+
+    fn push(
+        &mut self,
+        path: &[String],
+        kind: ChangeKind,
+        old: Option<&Value>,
+        new: Option<&Value>,
+    ) {
+        self.changes.push(Change {
+            path: path.join(":"),
+            kind,
+            old: old.cloned(),
+            new: new.cloned(),
+        });
+    }
+
+    fn compare(&mut self, path: &mut Vec<String>, old: &Value, new: &Value) {
+        match (old, new) {
+            (Value::Dictionary(old), Value::Dictionary(new)) => {
+                for (key, old) in old {
+                    path.push(key.clone());
+                    match new.get(key) {
+                        Some(new) => self.compare(path, old, new),
+                        None => self.push(path, ChangeKind::Removed, Some(old), None),
+                    }
+                    path.pop();
+                }
+                for (key, new) in new.iter().filter(|(key, _)| !old.contains_key(key)) {
+                    path.push(key.clone());
+                    self.push(path, ChangeKind::Added, None, Some(new));
+                    path.pop();
+                }
+            }
+            (Value::Array(old), Value::Array(new)) if is_set(path) => {
+                // Elements are matched one to one, so that duplicates count.
+                let mut unmatched: Vec<&Value> = new.iter().collect();
+                for value in old {
+                    match unmatched.iter().position(|other| *other == value) {
+                        Some(index) => {
+                            unmatched.remove(index);
+                        }
+                        None => self.push(path, ChangeKind::Removed, Some(value), None),
+                    }
+                }
+                for value in unmatched {
+                    self.push(path, ChangeKind::Added, None, Some(value));
+                }
+            }
+            (Value::Array(old), Value::Array(new)) => {
+                for index in 0..old.len().max(new.len()) {
+                    path.push(index.to_string());
+                    match (old.get(index), new.get(index)) {
+                        (Some(old), Some(new)) => self.compare(path, old, new),
+                        (Some(old), None) => self.push(path, ChangeKind::Removed, Some(old), None),
+                        (None, new) => self.push(path, ChangeKind::Added, None, new),
+                    }
+                    path.pop();
+                }
+            }
+            (old, new) if old != new => self.push(path, ChangeKind::Changed, Some(old), Some(new)),
+            _ => {}
+        }
+    }
+}
+
+/// Renders the changes as text for release notes, one per line, like
+/// `~ CFBundleVersion: "41" -> "42"`.
+impl fmt::Display for PlistDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            let path = match change.path.as_str() {
+                "" => "<root>",
+                path => path,
+            };
+            let sign = match change.kind {
+                ChangeKind::Added => '+',
+                ChangeKind::Removed => '-',
+                ChangeKind::Changed => '~',
+            };
+            write!(f, "{} {}", sign, path)?;
+            let mut separator = ": ";
+            for value in change.old.iter().chain(&change.new) {
+                write!(f, "{}{}", separator, DisplayValue(value))?;
+                separator = " -> ";
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns whether the array at `path` is one of the [`SET_KEYS`].
+fn is_set(path: &[String]) -> bool {
+    path.last()
+        .is_some_and(|key| SET_KEYS.contains(&key.as_str()))
+}
+
+struct DisplayValue<'a>(&'a Value);
+
+impl fmt::Display for DisplayValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Value::Array(array) => {
+                f.write_str("[")?;
+                for (index, value) in array.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}", DisplayValue(value))?;
+                }
+                f.write_str("]")
+            }
+            Value::Dictionary(dictionary) => {
+                f.write_str("{")?;
+                for (index, (key, value)) in dictionary.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}: {}", key, DisplayValue(value))?;
+                }
+                f.write_str("}")
+            }
+            Value::String(string) => write!(f, "{:?}", string),
+            Value::Boolean(value) => write!(f, "{}", value),
+            Value::Integer(integer) => write!(f, "{}", integer),
+            Value::Real(real) => write!(f, "{}", real),
+            Value::Date(date) => f.write_str(&date.to_xml_format()),
+            Value::Data(data) => {
+                f.write_str("<")?;
+                for byte in data {
+                    write!(f, "{:02x}", byte)?;
+                }
+                f.write_str(">")
+            }
+            value => write!(f, "{:?}", value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn diffs_info_plists() {
+        let old = InfoPlist {
+            identification: Identification {
+                bundle_identifier: "com.example.app".to_owned(),
+                ..Default::default()
+            },
+            bundle_version: BundleVersion {
+                bundle_version: Some("41".to_owned()),
+                ..Default::default()
+            },
+            background_execution: BackgroundExecution {
+                ui_background_modes: Some(vec![UiBackgroundMode::Audio, UiBackgroundMode::Voip]),
+                ..Default::default()
+            },
+            url_schemes: UrlSchemes {
+                bundle_url_types: Some(vec![BundleUrlTypes {
+                    bundle_url_name: "com.example.app".to_owned(),
+                    bundle_url_schemes: Some(vec!["example".to_owned()]),
+                    ..Default::default()
+                }]),
+            },
+            ..Default::default()
+        };
+        let mut new = old.clone();
+        new.bundle_version.bundle_version = Some("42".to_owned());
+        new.background_execution.ui_background_modes =
+            Some(vec![UiBackgroundMode::Location, UiBackgroundMode::Audio]);
+        new.url_schemes.bundle_url_types = Some(vec![
+            BundleUrlTypes {
+                bundle_url_name: "com.example.app".to_owned(),
+                bundle_url_schemes: Some(vec!["example".to_owned(), "ex".to_owned()]),
+                ..Default::default()
+            },
+            BundleUrlTypes {
+                bundle_url_name: "com.example.other".to_owned(),
+                ..Default::default()
+            },
+        ]);
+        new.camera_and_microphone.camera_usage_description = Some("Scans codes".to_owned());
+
+        assert!(PlistDiff::new(&old, &old.clone()).unwrap().is_empty());
+        let diff = PlistDiff::new(&old, &new).unwrap();
+        assert_eq!(
+            diff.to_string(),
+            r#"~ CFBundleVersion: "41" -> "42"
+- UIBackgroundModes: "voip"
++ UIBackgroundModes: "location"
++ CFBundleURLTypes:0:CFBundleURLSchemes: "ex"
++ CFBundleURLTypes:1: {CFBundleURLName: "com.example.other"}
++ NSCameraUsageDescription: "Scans codes"
+"#
+        );
+        assert_eq!(
+            diff.changes[0],
+            Change {
+                path: "CFBundleVersion".to_owned(),
+                kind: ChangeKind::Changed,
+                old: Some("41".into()),
+                new: Some("42".into()),
+            }
+        );
+
+        #[cfg(feature = "serde_json")]
+        {
+            let json: serde_json::Value = serde_json::from_str(&diff.to_json().unwrap()).unwrap();
+            assert_eq!(
+                json[1],
+                serde_json::json!({
+                    "path": "UIBackgroundModes",
+                    "kind": "removed",
+                    "old": "voip",
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn compares_only_set_keys_as_sets() {
+        let strings = |values: &[&str]| Value::Array(values.iter().map(|&v| v.into()).collect());
+        let mut old = plist::Dictionary::new();
+        old.insert("UIBackgroundModes".to_owned(), strings(&["audio", "audio"]));
+        old.insert(
+            "UISupportedInterfaceOrientations".to_owned(),
+            strings(&[
+                "UIInterfaceOrientationPortrait",
+                "UIInterfaceOrientationLandscapeLeft",
+            ]),
+        );
+        let mut new = plist::Dictionary::new();
+        new.insert("UIBackgroundModes".to_owned(), strings(&["audio"]));
+        new.insert(
+            "UISupportedInterfaceOrientations".to_owned(),
+            strings(&[
+                "UIInterfaceOrientationLandscapeLeft",
+                "UIInterfaceOrientationPortrait",
+            ]),
+        );
+        let diff = PlistDiff::between(&Value::Dictionary(old), &Value::Dictionary(new));
+        assert_eq!(
+            diff.to_string(),
+            r#"- UIBackgroundModes: "audio"
+~ UISupportedInterfaceOrientations:0: "UIInterfaceOrientationPortrait" -> "UIInterfaceOrientationLandscapeLeft"
+~ UISupportedInterfaceOrientations:1: "UIInterfaceOrientationLandscapeLeft" -> "UIInterfaceOrientationPortrait"
+"#
+        );
+    }
+}
@@ -0,0 +1,753 @@
+//! # Property List Document.
+//!
+//! Format-preserving editing of XML property lists.
+//!
+//! A [`PlistDocument`](crate::plist_document::PlistDocument) keeps the text of the file
+//! it was read from. Values are read, set and removed by key path, and only the bytes of
+//! the changed values are rewritten, so the order of keys, the indentation, comments and
+//! keys this crate doesn't model stay as they are.
+//!
+//! Key paths separate dictionary keys and array indices with `:`, like `PlistBuddy`
+//! does, as in `CFBundleURLTypes:0:CFBundleURLSchemes`. The empty path is the root.
+
+use crate::error::{Error, Result};
+use plist::Value;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{fmt, path::Path, str::FromStr};
+
+/// The deepest nesting of dictionaries and arrays parsed, so that malformed input
+/// can't overflow the stack.
+const MAX_DEPTH: usize = 128;
+
+/// An XML property list that can be edited without rewriting the untouched parts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlistDocument {
+    source: String,
+    root: Node,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Node {
+    /// The byte offset of the `<` of the element.
+    start: usize,
+    /// The byte offset after the `>` of the element.
+    end: usize,
+    kind: NodeKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum NodeKind {
+    Dictionary(Vec<Entry>),
+    Array(Vec<Node>),
+    Scalar,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Entry {
+    key: String,
+    /// The byte offset of the `<key>` element.
+    key_start: usize,
+    value: Node,
+}
+
+impl Node {
+    /// Returns the byte offset where the children of the container start.
+    fn first_child_start(&self) -> Option<usize> {
+        match &self.kind {
+            NodeKind::Dictionary(entries) => entries.first().map(|entry| entry.key_start),
+            NodeKind::Array(items) => items.first().map(|item| item.start),
+            NodeKind::Scalar => None,
+        }
+    }
+
+    /// Returns the byte offset where the last child of the container ends.
+    fn last_child_end(&self) -> Option<usize> {
+        match &self.kind {
+            NodeKind::Dictionary(entries) => entries.last().map(|entry| entry.value.end),
+            NodeKind::Array(items) => items.last().map(|item| item.end),
+            NodeKind::Scalar => None,
+        }
+    }
+}
+
+impl PlistDocument {
+    /// Parses the text of an XML property list.
+    pub fn parse(source: impl Into<String>) -> Result<Self> {
+        let source = source.into();
+        let root = XmlParser {
+            source: &source,
+            position: 0,
+        }
+        .parse_document()?;
+        Ok(Self { source, root })
+    }
+
+    /// Reads the XML property list file at `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::parse(std::fs::read_to_string(path)?)
+    }
+
+    /// Writes the document to `path`.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        Ok(std::fs::write(path, &self.source)?)
+    }
+
+    /// Returns the text of the document.
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Returns the value at a key path, or `None` when a key or an index is missing.
+    pub fn get(&self, path: &str) -> Result<Option<Value>> {
+        match self.find(&segments(path))? {
+            Some(node) => Ok(Some(self.node_value(node)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the value at a key path deserialized as `T`, like `Vec<String>` or
+    /// `BundleUrlTypes`.
+    pub fn get_as<T: DeserializeOwned>(&self, path: &str) -> Result<Option<T>> {
+        match self.get(path)? {
+            Some(value) => Ok(Some(plist::from_value(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Deserializes the whole document, like an `InfoPlist` or `Entitlements`.
+    pub fn deserialize<T: DeserializeOwned>(&self) -> Result<T> {
+        Ok(plist::from_bytes(self.source.as_bytes())?)
+    }
+
+    /// Sets the value at a key path.
+    ///
+    /// Missing dictionaries along the path are created, and an index equal to the
+    /// length of an array appends to it.
+    pub fn set(&mut self, path: &str, value: impl Into<Value>) -> Result<()> {
+        self.set_segments(&segments(path), value.into())
+    }
+
+    /// Sets the value at a key path to a serialized value.
+    pub fn set_as<T: Serialize>(&mut self, path: &str, value: &T) -> Result<()> {
+        self.set(path, plist::to_value(value)?)
+    }
+
+    /// Removes the value at a key path and returns it.
+    pub fn remove(&mut self, path: &str) -> Result<Option<Value>> {
+        self.remove_segments(&segments(path))
+    }
+
+    /// Deserializes the document as `T`, lets `edit` change it, and writes back the
+    /// values that changed.
+    ///
+    /// Keys that `T` doesn't model, and values that `edit` doesn't change, are kept
+    /// as they are.
+    pub fn update<T: Serialize + DeserializeOwned>(
+        &mut self,
+        edit: impl FnOnce(&mut T),
+    ) -> Result<()> {
+        let mut value: T = self.deserialize()?;
+        let before = plist::to_value(&value)?;
+        edit(&mut value);
+        let after = plist::to_value(&value)?;
+        self.apply(&mut Vec::new(), &before, &after)
+    }
+
+    fn apply(&mut self, path: &mut Vec<String>, before: &Value, after: &Value) -> Result<()> {
+        if before == after {
+            return Ok(());
+        }
+        let (Value::Dictionary(before), Value::Dictionary(after)) = (before, after) else {
+            let segments: Vec<&str> = path.iter().map(String::as_str).collect();
+            return self.set_segments(&segments, after.clone());
+        };
+        for key in before.keys().filter(|key| !after.contains_key(key)) {
+            path.push(key.clone());
+            let segments: Vec<&str> = path.iter().map(String::as_str).collect();
+            self.remove_segments(&segments)?;
+            path.pop();
+        }
+        for (key, value) in after {
+            path.push(key.clone());
+            match before.get(key) {
+                Some(previous) => self.apply(path, previous, value)?,
+                None => {
+                    let segments: Vec<&str> = path.iter().map(String::as_str).collect();
+                    self.set_segments(&segments, value.clone())?;
+                }
+            }
+            path.pop();
+        }
+        Ok(())
+    }
+
+    fn find(&self, segments: &[&str]) -> Result<Option<&Node>> {
+        let mut node = &self.root;
+        for (depth, segment) in segments.iter().enumerate() {
+            let child = match &node.kind {
+                NodeKind::Dictionary(entries) => entries
+                    .iter()
+                    .find(|entry| entry.key == *segment)
+                    .map(|entry| &entry.value),
+                NodeKind::Array(items) => items.get(index(segments, depth)?),
+                NodeKind::Scalar => return Err(not_a_container(segments, depth)),
+            };
+            match child {
+                Some(child) => node = child,
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(node))
+    }
+
+    fn node_value(&self, node: &Node) -> Result<Value> {
+        let xml = format!(
+            "<plist version=\"1.0\">{}</plist>",
+            &self.source[node.start..node.end]
+        );
+        Ok(Value::from_reader_xml(xml.as_bytes())?)
+    }
+
+    fn set_segments(&mut self, segments: &[&str], value: Value) -> Result<()> {
+        let mut node = &self.root;
+        for (depth, segment) in segments.iter().enumerate() {
+            let child = match &node.kind {
+                NodeKind::Dictionary(entries) => entries
+                    .iter()
+                    .find(|entry| entry.key == *segment)
+                    .map(|entry| &entry.value),
+                NodeKind::Array(items) => {
+                    let index = index(segments, depth)?;
+                    if index > items.len() {
+                        return Err(Error::InvalidKeyPath(format!(
+                            "index {} of {} is out of bounds",
+                            index,
+                            segments[..depth].join(":")
+                        )));
+                    }
+                    items.get(index)
+                }
+                NodeKind::Scalar => return Err(not_a_container(segments, depth)),
+            };
+            match child {
+                Some(child) => node = child,
+                None => {
+                    let value = segments[depth + 1..]
+                        .iter()
+                        .rev()
+                        .fold(value, |value, key| {
+                            let mut dictionary = plist::Dictionary::new();
+                            dictionary.insert((*key).to_owned(), value);
+                            Value::Dictionary(dictionary)
+                        });
+                    let key = matches!(node.kind, NodeKind::Dictionary(_)).then_some(*segment);
+                    let (start, end, text) = self.insertion(node, key, &value)?;
+                    return self.splice(start, end, &text);
+                }
+            }
+        }
+        let (start, end) = (node.start, node.end);
+        let text = self.fragment(&value, self.line_indent(start))?;
+        self.splice(start, end, &text)
+    }
+
+    fn remove_segments(&mut self, segments: &[&str]) -> Result<Option<Value>> {
+        let Some((last, parent)) = segments.split_last() else {
+            return Err(Error::InvalidKeyPath(
+                "the root of a document can't be removed".to_owned(),
+            ));
+        };
+        let Some(parent) = self.find(parent)? else {
+            return Ok(None);
+        };
+        let child = match &parent.kind {
+            NodeKind::Dictionary(entries) => entries
+                .iter()
+                .find(|entry| entry.key == *last)
+                .map(|entry| (entry.key_start, &entry.value)),
+            NodeKind::Array(items) => items
+                .get(index(segments, segments.len() - 1)?)
+                .map(|item| (item.start, item)),
+            NodeKind::Scalar => return Err(not_a_container(segments, segments.len() - 1)),
+        };
+        let Some((start, node)) = child else {
+            return Ok(None);
+        };
+        let value = self.node_value(node)?;
+        let end = node.end;
+        // Removes the line of the value as well when nothing else is on it.
+        let line_start = self.source[..start].rfind('\n').unwrap_or(0);
+        let start = if self.source[line_start..start].trim().is_empty() {
+            line_start
+        } else {
+            start
+        };
+        self.splice(start, end, "")?;
+        Ok(Some(value))
+    }
+
+    /// Returns the range to replace and the text that adds `value` as the last child
+    /// of a container, under `key` for dictionaries.
+    fn insertion(
+        &self,
+        container: &Node,
+        key: Option<&str>,
+        value: &Value,
+    ) -> Result<(usize, usize, String)> {
+        let indent = match container.first_child_start() {
+            Some(start) => self.line_indent(start).to_owned(),
+            None => format!(
+                "{}{}",
+                self.line_indent(container.start),
+                self.indent_unit()
+            ),
+        };
+        let mut child = String::new();
+        if let Some(key) = key {
+            child.push_str(&format!("\n{}<key>{}</key>", indent, escape(key)));
+        }
+        child.push_str(&format!("\n{}{}", indent, self.fragment(value, &indent)?));
+        Ok(match container.last_child_end() {
+            Some(end) => (end, end, child),
+            None => {
+                let name = match container.kind {
+                    NodeKind::Dictionary(_) => "dict",
+                    _ => "array",
+                };
+                let text = format!(
+                    "<{name}>{child}\n{}</{name}>",
+                    self.line_indent(container.start)
+                );
+                (container.start, container.end, text)
+            }
+        })
+    }
+
+    fn splice(&mut self, start: usize, end: usize, text: &str) -> Result<()> {
+        let mut source = self.source.clone();
+        source.replace_range(start..end, text);
+        *self = Self::parse(source)?;
+        Ok(())
+    }
+
+    /// Returns the whitespace before `position` on its line, or nothing when the line
+    /// has other text before it.
+    fn line_indent(&self, position: usize) -> &str {
+        let line_start = self.source[..position]
+            .rfind('\n')
+            .map_or(0, |index| index + 1);
+        let indent = &self.source[line_start..position];
+        if indent.trim().is_empty() {
+            indent
+        } else {
+            ""
+        }
+    }
+
+    /// Returns the indentation of one level, from the children of the root, or a tab.
+    fn indent_unit(&self) -> &str {
+        let root_indent = self.line_indent(self.root.start);
+        self.root
+            .first_child_start()
+            .and_then(|start| self.line_indent(start).strip_prefix(root_indent))
+            .filter(|unit| !unit.is_empty())
+            .unwrap_or("\t")
+    }
+
+    /// Writes a value as XML whose lines after the first are indented by `indent`.
+    fn fragment(&self, value: &Value, indent: &str) -> Result<String> {
+        let inner = format!("{}{}", indent, self.indent_unit());
+        let mut text = String::new();
+        match value {
+            Value::Dictionary(dictionary) if !dictionary.is_empty() => {
+                text.push_str("<dict>");
+                for (key, value) in dictionary {
+                    text.push_str(&format!("\n{}<key>{}</key>", inner, escape(key)));
+                    text.push_str(&format!("\n{}{}", inner, self.fragment(value, &inner)?));
+                }
+                text.push_str(&format!("\n{}</dict>", indent));
+            }
+            Value::Array(array) if !array.is_empty() => {
+                text.push_str("<array>");
+                for value in array {
+                    text.push_str(&format!("\n{}{}", inner, self.fragment(value, &inner)?));
+                }
+                text.push_str(&format!("\n{}</array>", indent));
+            }
+            value => {
+                let mut xml = Vec::new();
+                plist::to_writer_xml(&mut xml, value)?;
+                let xml = String::from_utf8_lossy(&xml);
+                let start = xml
+                    .find("<plist version=\"1.0\">")
+                    .map_or(0, |start| start + 21);
+                let end = xml.rfind("</plist>").unwrap_or(xml.len());
+                text.push_str(xml[start..end].trim());
+            }
+        }
+        Ok(text)
+    }
+}
+
+impl FromStr for PlistDocument {
+    type Err = Error;
+
+    fn from_str(source: &str) -> Result<Self> {
+        Self::parse(source)
+    }
+}
+
+impl fmt::Display for PlistDocument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+/// Splits a key path into its keys and indices.
+fn segments(path: &str) -> Vec<&str> {
+    match path {
+        "" => Vec::new(),
+        path => path.split(':').collect(),
+    }
+}
+
+fn index(segments: &[&str], depth: usize) -> Result<usize> {
+    segments[depth].parse().map_err(|_| {
+        Error::InvalidKeyPath(format!(
+            "{} is an array, but `{}` isn't an index",
+            segments[..depth].join(":"),
+            segments[depth]
+        ))
+    })
+}
+
+fn not_a_container(segments: &[&str], depth: usize) -> Error {
+    Error::InvalidKeyPath(format!(
+        "{} is neither a dictionary nor an array",
+        segments[..depth].join(":")
+    ))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn unescape(text: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        let end = rest[start..].find(';')? + start;
+        let c = match &rest[start + 1..end] {
+            "amp" => '&',
+            "lt" => '<',
+            "gt" => '>',
+            "quot" => '"',
+            "apos" => '\'',
+            entity => {
+                let code = match entity.strip_prefix("#x") {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                    None => entity.strip_prefix('#')?.parse().ok()?,
+                };
+                char::from_u32(code)?
+            }
+        };
+        unescaped.push(c);
+        rest = &rest[end + 1..];
+    }
+    unescaped.push_str(rest);
+    Some(unescaped)
+}
+
+/// Finds the elements of an XML property list and their byte offsets.
+struct XmlParser<'a> {
+    source: &'a str,
+    position: usize,
+}
+
+impl<'a> XmlParser<'a> {
+    fn error(&self, reason: &str) -> Error {
+        let line = self.source[..self.position].matches('\n').count();
+        Error::InvalidXmlPlist(format!("line {}: {}", line + 1, reason))
+    }
+
+    fn rest(&self) -> &str {
+        &self.source[self.position..]
+    }
+
+    /// Skips to after `pattern`.
+    fn skip_past(&mut self, pattern: &str) -> Result<()> {
+        let end = self
+            .rest()
+            .find(pattern)
+            .ok_or_else(|| self.error(&format!("expected `{}`", pattern)))?;
+        self.position += end + pattern.len();
+        Ok(())
+    }
+
+    /// Skips whitespace, comments, processing instructions and the document type.
+    fn skip_misc(&mut self) -> Result<()> {
+        loop {
+            let source = self.source;
+            let rest = &source[self.position..];
+            let trimmed = rest.trim_start();
+            self.position += rest.len() - trimmed.len();
+            if trimmed.starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if trimmed.starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if trimmed.starts_with("<!") {
+                self.skip_past(">")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn parse_document(&mut self) -> Result<Node> {
+        self.skip_misc()?;
+        let (name, self_closing) = self.read_tag()?;
+        if name != "plist" || self_closing {
+            return Err(self.error("expected `<plist>`"));
+        }
+        let root = self.parse_element(0)?;
+        self.skip_misc()?;
+        self.read_close("plist")?;
+        self.skip_misc()?;
+        if !self.rest().is_empty() {
+            return Err(self.error("unexpected data after `</plist>`"));
+        }
+        Ok(root)
+    }
+
+    /// Reads an opening tag and returns its name and whether it's self-closing.
+    fn read_tag(&mut self) -> Result<(&'a str, bool)> {
+        let source = self.source;
+        let rest = &source[self.position..];
+        if !rest.starts_with('<') || rest.starts_with("</") {
+            return Err(self.error("expected an element"));
+        }
+        let end = rest
+            .find('>')
+            .ok_or_else(|| self.error("unterminated tag"))?;
+        let tag = &rest[1..end];
+        let self_closing = tag.ends_with('/');
+        let name = tag
+            .trim_end_matches('/')
+            .split(|c: char| c.is_whitespace())
+            .next()
+            .unwrap_or_default();
+        self.position += end + 1;
+        Ok((name, self_closing))
+    }
+
+    fn read_close(&mut self, name: &str) -> Result<()> {
+        let tag = format!("</{}", name);
+        if !self.rest().starts_with(&tag) {
+            return Err(self.error(&format!("expected `</{}>`", name)));
+        }
+        self.skip_past(">")
+    }
+
+    fn is_close(&mut self) -> Result<bool> {
+        self.skip_misc()?;
+        Ok(self.rest().starts_with("</"))
+    }
+
+    /// Parses an element nested in `depth` dictionaries and arrays.
+    fn parse_element(&mut self, depth: usize) -> Result<Node> {
+        if depth == MAX_DEPTH {
+            return Err(self.error("elements nested too deeply"));
+        }
+        self.skip_misc()?;
+        let start = self.position;
+        let (name, self_closing) = self.read_tag()?;
+        let kind = match name {
+            "dict" => {
+                let mut entries = Vec::new();
+                while !self_closing && !self.is_close()? {
+                    let key_start = self.position;
+                    let (name, self_closing) = self.read_tag()?;
+                    if name != "key" {
+                        return Err(self.error("expected `<key>`"));
+                    }
+                    let mut key = String::new();
+                    if !self_closing {
+                        let text_start = self.position;
+                        self.skip_past("</key")?;
+                        let text = &self.source[text_start..self.position - 5];
+                        self.skip_past(">")?;
+                        key = unescape(text).ok_or_else(|| self.error("invalid entity"))?;
+                    }
+                    let value = self.parse_element(depth + 1)?;
+                    entries.push(Entry {
+                        key,
+                        key_start,
+                        value,
+                    });
+                }
+                if !self_closing {
+                    self.read_close("dict")?;
+                }
+                NodeKind::Dictionary(entries)
+            }
+            "array" => {
+                let mut items = Vec::new();
+                while !self_closing && !self.is_close()? {
+                    items.push(self.parse_element(depth + 1)?);
+                }
+                if !self_closing {
+                    self.read_close("array")?;
+                }
+                NodeKind::Array(items)
+            }
+            "string" | "integer" | "real" | "date" | "data" | "true" | "false" => {
+                if !self_closing {
+                    let close = format!("</{}", name);
+                    self.skip_past(&close)?;
+                    self.skip_past(">")?;
+                }
+                NodeKind::Scalar
+            }
+            _ => return Err(self.error(&format!("unknown element `{}`", name))),
+        };
+        Ok(Node {
+            start,
+            end: self.position,
+            kind,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    const INFO_PLIST: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+  <!-- Set by the release script. -->
+  <key>CFBundleVersion</key>
+  <string>41</string>
+  <key>CFBundleIdentifier</key>
+  <string>com.example.app</string>
+  <key>CustomKey</key>
+  <dict>
+    <key>Nested &amp; escaped</key>
+    <true/>
+  </dict>
+  <key>UIDeviceFamily</key>
+  <array>
+    <integer>1</integer>
+  </array>
+  <key>CFBundleURLTypes</key>
+  <array/>
+</dict>
+</plist>
+"#;
+
+    #[test]
+    fn edits_values_in_place() {
+        let mut document = PlistDocument::parse(INFO_PLIST).unwrap();
+        assert_eq!(
+            document.get("CFBundleVersion").unwrap(),
+            Some(Value::String("41".to_owned()))
+        );
+        assert_eq!(
+            document.get("CustomKey:Nested & escaped").unwrap(),
+            Some(Value::Boolean(true))
+        );
+        assert_eq!(
+            document.get_as::<Vec<u8>>("UIDeviceFamily").unwrap(),
+            Some(vec![1])
+        );
+        assert_eq!(document.get("Missing:Key").unwrap(), None);
+        assert!(document.get("CFBundleVersion:0").is_err());
+        assert!(document.get("UIDeviceFamily:first").is_err());
+
+        document.set("CFBundleVersion", "42").unwrap();
+        assert_eq!(
+            document.as_str(),
+            INFO_PLIST.replace("<string>41</string>", "<string>42</string>")
+        );
+
+        document.set("UIDeviceFamily:1", 2).unwrap();
+        document
+            .set("NSCameraUsageDescription", "Scans codes")
+            .unwrap();
+        document
+            .set("CFBundleURLTypes:0:CFBundleURLName", "example")
+            .unwrap();
+        assert!(document.remove("CustomKey").unwrap().is_some());
+        assert_eq!(document.remove("CustomKey").unwrap(), None);
+        assert!(document.remove("").is_err());
+        assert!(document.set("UIDeviceFamily:5", 3).is_err());
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+  <!-- Set by the release script. -->
+  <key>CFBundleVersion</key>
+  <string>42</string>
+  <key>CFBundleIdentifier</key>
+  <string>com.example.app</string>
+  <key>UIDeviceFamily</key>
+  <array>
+    <integer>1</integer>
+    <integer>2</integer>
+  </array>
+  <key>CFBundleURLTypes</key>
+  <array>
+    <dict>
+      <key>CFBundleURLName</key>
+      <string>example</string>
+    </dict>
+  </array>
+  <key>NSCameraUsageDescription</key>
+  <string>Scans codes</string>
+</dict>
+</plist>
+"#;
+        assert_eq!(document.as_str(), expected);
+        assert_eq!(
+            plist::from_bytes::<Value>(expected.as_bytes()).unwrap(),
+            document.get("").unwrap().unwrap()
+        );
+    }
+
+    #[test]
+    fn updates_typed_values() {
+        let mut document = PlistDocument::parse(INFO_PLIST).unwrap();
+        document
+            .update(|info_plist: &mut InfoPlist| {
+                info_plist.bundle_version.bundle_version = Some("42".to_owned());
+                info_plist.background_execution.ui_device_family = None;
+            })
+            .unwrap();
+        let expected = INFO_PLIST
+            .replace("<string>41</string>", "<string>42</string>")
+            .replace(
+                "\n  <key>UIDeviceFamily</key>\n  <array>\n    <integer>1</integer>\n  </array>",
+                "",
+            );
+        assert_eq!(document.as_str(), expected);
+        let info_plist: InfoPlist = document.deserialize().unwrap();
+        assert_eq!(
+            info_plist.identification.bundle_identifier,
+            "com.example.app"
+        );
+    }
+
+    #[test]
+    fn rejects_deeply_nested_elements() {
+        let source = format!("<plist>{}", "<array>".repeat(200_000));
+        assert!(matches!(
+            PlistDocument::parse(source),
+            Err(Error::InvalidXmlPlist(_))
+        ));
+    }
+}
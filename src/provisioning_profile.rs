@@ -0,0 +1,586 @@
+//! # Provisioning Profile.
+//!
+//! A signed property list that authorizes an app to run on devices and use services.
+//!
+//! Provisioning profiles, stored as `.mobileprovision` files for iOS, tvOS and
+//! watchOS and as `.provisionprofile` files for macOS, tie together a team, an app ID,
+//! the certificates allowed to sign the app, the devices it may run on and the
+//! entitlements it may claim. The property list is wrapped in a CMS `SignedData`
+//! envelope signed by Apple. An app embeds the profile it was signed with as
+//! `embedded.mobileprovision` or `Contents/embedded.provisionprofile`.
+//!
+//! Official documentation: <https://developer.apple.com/documentation/technotes/tn3125-inside-code-signing-provisioning-profiles>
+
+use crate::{
+    der::{self, Reader},
+    entitlements::Entitlements,
+    error::{Error, Result},
+};
+use plist::{Data, Date, Dictionary, Value};
+use serde::Deserialize;
+use std::{borrow::Cow, path::Path, time::SystemTime};
+
+/// Object identifier of the CMS `SignedData` content type, 1.2.840.113549.1.7.2.
+const SIGNED_DATA_OID: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x07, 0x02];
+
+/// The property list of a provisioning profile.
+///
+/// The signature of the envelope isn't verified.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(from = "RawProvisioningProfile")]
+pub struct ProvisioningProfile {
+    /// The name of the app ID the profile was created for.
+    pub app_id_name: String,
+    /// The prefixes of the app ID, usually the team ID.
+    pub application_identifier_prefix: Vec<String>,
+    /// The date the profile was created.
+    pub creation_date: Date,
+    /// The platforms the profile can be used on, such as `iOS` or `OSX`.
+    pub platform: Vec<String>,
+    /// Whether Xcode manages the profile.
+    pub is_xcode_managed: Option<bool>,
+    /// The DER-encoded certificates allowed to sign apps with this profile.
+    pub developer_certificates: Vec<Data>,
+    /// The entitlements the profile authorizes, as stored in the profile.
+    ///
+    /// Authorized values may contain wildcards, like `<team id>.*` or a `*` string in
+    /// place of an array, which [`Entitlements`] can't represent.
+    pub authorized_entitlements: Dictionary,
+    /// The authorized entitlements that fit [`Entitlements`].
+    ///
+    /// Entitlements whose authorized value is a wildcard or otherwise doesn't match the
+    /// typed field are left unset.
+    pub entitlements: Entitlements,
+    /// The date the profile stops being valid.
+    pub expiration_date: Date,
+    /// The name of the profile.
+    pub name: String,
+    /// The UDIDs of the devices the profile allows the app to run on.
+    pub provisioned_devices: Option<Vec<String>>,
+    /// Whether the profile allows the app to run on any device, as for enterprise and
+    /// Developer ID profiles.
+    pub provisions_all_devices: Option<bool>,
+    /// The identifiers of the team the profile belongs to.
+    pub team_identifier: Vec<String>,
+    /// The name of the team the profile belongs to.
+    pub team_name: String,
+    /// The number of days the profile is valid for.
+    pub time_to_live: u64,
+    /// The unique identifier of the profile.
+    pub uuid: String,
+    /// The version of the profile format.
+    pub version: u64,
+}
+
+impl ProvisioningProfile {
+    /// Parses a provisioning profile from the contents of a `.mobileprovision` or
+    /// `.provisionprofile` file.
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        let plist = Self::extract_plist(data)?;
+        Ok(plist::from_bytes(&plist)?)
+    }
+
+    /// Reads and parses a provisioning profile file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    /// Extracts the property list embedded in the CMS `SignedData` envelope of a
+    /// provisioning profile, without verifying the signature.
+    pub fn extract_plist(data: &[u8]) -> Result<Cow<'_, [u8]>> {
+        let content_info = Reader::new(data).expect(der::SEQUENCE)?;
+        let mut content_info = content_info.children();
+        let content_type = content_info.expect(der::OBJECT_IDENTIFIER)?;
+        if content_type.contents != SIGNED_DATA_OID {
+            return Err(Error::InvalidDer(
+                "provisioning profile isn't CMS signed data".to_owned(),
+            ));
+        }
+        let content = content_info.expect(der::CONTEXT_0)?;
+        let signed_data = content.children().expect(der::SEQUENCE)?;
+        let mut signed_data = signed_data.children();
+        signed_data.expect(der::INTEGER)?;
+        signed_data.expect(der::SET)?;
+        let encapsulated = signed_data.expect(der::SEQUENCE)?;
+        let mut encapsulated = encapsulated.children();
+        encapsulated.expect(der::OBJECT_IDENTIFIER)?;
+        let content = encapsulated.expect(der::CONTEXT_0)?;
+        content.children().read()?.octets()
+    }
+
+    /// Returns `true` if the profile is expired at the given time.
+    pub fn is_expired_at(&self, time: SystemTime) -> bool {
+        SystemTime::from(self.expiration_date) <= time
+    }
+
+    /// Returns the entitlements requested by the app that this profile doesn't
+    /// authorize.
+    ///
+    /// An empty list means code signing the app with this profile won't be rejected
+    /// because of its entitlements. See [`uncovered_entitlements`] for the matching
+    /// rules.
+    pub fn check_entitlements(
+        &self,
+        entitlements: &Entitlements,
+    ) -> Result<Vec<UncoveredEntitlement>> {
+        let requested = match plist::to_value(entitlements)? {
+            Value::Dictionary(requested) => requested,
+            _ => Dictionary::new(),
+        };
+        Ok(uncovered_entitlements(
+            &requested,
+            &self.authorized_entitlements,
+        ))
+    }
+
+    /// Returns `true` if the profile allows the app to run on the device with the given
+    /// UDID.
+    pub fn provisions_device(&self, udid: &str) -> bool {
+        self.provisions_all_devices == Some(true)
+            || self
+                .provisioned_devices
+                .iter()
+                .flatten()
+                .any(|device| device.eq_ignore_ascii_case(udid))
+    }
+}
+
+/// The App Sandbox and Hardened Runtime entitlements an app can enable without being
+/// authorized by a provisioning profile.
+pub const UNRESTRICTED_ENTITLEMENTS: &[&str] = &[
+    "com.apple.security.app-sandbox",
+    "com.apple.security.assets.movies.read-only",
+    "com.apple.security.assets.movies.read-write",
+    "com.apple.security.assets.music.read-only",
+    "com.apple.security.assets.music.read-write",
+    "com.apple.security.assets.pictures.read-only",
+    "com.apple.security.assets.pictures.read-write",
+    "com.apple.security.automation.apple-events",
+    "com.apple.security.cs.allow-dyld-environment-variables",
+    "com.apple.security.cs.allow-jit",
+    "com.apple.security.cs.allow-unsigned-executable-memory",
+    "com.apple.security.cs.debugger",
+    "com.apple.security.cs.disable-executable-page-protection",
+    "com.apple.security.cs.disable-library-validation",
+    "com.apple.security.device.audio-input",
+    "com.apple.security.device.bluetooth",
+    "com.apple.security.device.camera",
+    "com.apple.security.device.microphone",
+    "com.apple.security.device.usb",
+    "com.apple.security.files.all",
+    "com.apple.security.files.downloads.read-only",
+    "com.apple.security.files.downloads.read-write",
+    "com.apple.security.files.user-selected.read-only",
+    "com.apple.security.files.user-selected.read-write",
+    "com.apple.security.get-task-allow",
+    "com.apple.security.hypervisor",
+    "com.apple.security.network.client",
+    "com.apple.security.network.server",
+    "com.apple.security.personal-information.addressbook",
+    "com.apple.security.personal-information.calendars",
+    "com.apple.security.personal-information.location",
+    "com.apple.security.personal-information.photos-library",
+    "com.apple.security.print",
+    "com.apple.security.smartcard",
+    "com.apple.security.virtualization",
+];
+
+/// An entitlement requested by an app that a provisioning profile doesn't authorize.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UncoveredEntitlement {
+    /// The entitlement key.
+    pub key: String,
+    /// The requested value that isn't covered. For arrays, only the uncovered element.
+    pub requested: Value,
+    /// The value authorized by the profile, if the profile has the entitlement at all.
+    pub authorized: Option<Value>,
+}
+
+/// Compares requested entitlements against the entitlements authorized by a
+/// provisioning profile and returns every requested entitlement that isn't covered.
+///
+/// * A `*` string authorizes any value, and a string ending with `*`, such as `<team
+///   id>.*` in `application-identifier` or `keychain-access-groups`, authorizes every
+///   string starting with the same prefix.
+/// * Every element of a requested array, such as iCloud containers or associated domains,
+///   must be authorized by an element of the authorized array, and a single requested
+///   value is authorized by any matching element of an authorized array.
+/// * Other strings, like `aps-environment` set to `development` or `production`, must be
+///   equal to the authorized value.
+/// * Booleans set to `false` never need authorization.
+/// * The App Sandbox and Hardened Runtime booleans in [`UNRESTRICTED_ENTITLEMENTS`] don't
+///   need to be authorized by a profile. Other entitlements in the `com.apple.security.`
+///   namespace, like app groups, do.
+pub fn uncovered_entitlements(
+    requested: &Dictionary,
+    authorized: &Dictionary,
+) -> Vec<UncoveredEntitlement> {
+    let mut uncovered = Vec::new();
+    for (key, requested) in requested {
+        if let Value::Boolean(false) = requested {
+            continue;
+        }
+        let authorized = match authorized.get(key) {
+            Some(authorized) => authorized,
+            None if UNRESTRICTED_ENTITLEMENTS.contains(&key.as_str()) => continue,
+            None => {
+                uncovered.push(UncoveredEntitlement {
+                    key: key.clone(),
+                    requested: requested.clone(),
+                    authorized: None,
+                });
+                continue;
+            }
+        };
+        let values = match requested {
+            Value::Array(values) if !is_wildcard(authorized) => values.as_slice(),
+            value => std::slice::from_ref(value),
+        };
+        for value in values {
+            if !covers(authorized, value) {
+                uncovered.push(UncoveredEntitlement {
+                    key: key.clone(),
+                    requested: value.clone(),
+                    authorized: Some(authorized.clone()),
+                });
+            }
+        }
+    }
+    uncovered
+}
+
+fn is_wildcard(value: &Value) -> bool {
+    value.as_string() == Some("*")
+}
+
+fn covers(authorized: &Value, requested: &Value) -> bool {
+    match (authorized, requested) {
+        (authorized, _) if is_wildcard(authorized) => true,
+        (Value::String(authorized), Value::String(requested)) => {
+            match authorized.strip_suffix('*') {
+                Some(prefix) => requested.starts_with(prefix),
+                None => authorized == requested,
+            }
+        }
+        (Value::Boolean(authorized), Value::Boolean(requested)) => *authorized || !*requested,
+        (Value::Array(authorized), Value::Array(requested)) => requested
+            .iter()
+            .all(|requested| authorized.iter().any(|a| covers(a, requested))),
+        (Value::Array(authorized), requested) => authorized
+            .iter()
+            .any(|authorized| covers(authorized, requested)),
+        (Value::Dictionary(authorized), Value::Dictionary(requested)) => {
+            requested.iter().all(|(key, requested)| {
+                authorized
+                    .get(key)
+                    .is_some_and(|authorized| covers(authorized, requested))
+            })
+        }
+        (authorized, requested) => authorized == requested,
+    }
+}
+
+#[derive(Deserialize)]
+struct RawProvisioningProfile {
+    #[serde(rename = "AppIDName")]
+    app_id_name: String,
+    #[serde(rename = "ApplicationIdentifierPrefix", default)]
+    application_identifier_prefix: Vec<String>,
+    #[serde(rename = "CreationDate")]
+    creation_date: Date,
+    #[serde(rename = "Platform", default)]
+    platform: Vec<String>,
+    #[serde(rename = "IsXcodeManaged")]
+    is_xcode_managed: Option<bool>,
+    #[serde(rename = "DeveloperCertificates", default)]
+    developer_certificates: Vec<Data>,
+    #[serde(rename = "Entitlements", default)]
+    entitlements: Dictionary,
+    #[serde(rename = "ExpirationDate")]
+    expiration_date: Date,
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "ProvisionedDevices")]
+    provisioned_devices: Option<Vec<String>>,
+    #[serde(rename = "ProvisionsAllDevices")]
+    provisions_all_devices: Option<bool>,
+    #[serde(rename = "TeamIdentifier", default)]
+    team_identifier: Vec<String>,
+    #[serde(rename = "TeamName")]
+    team_name: String,
+    #[serde(rename = "TimeToLive")]
+    time_to_live: u64,
+    #[serde(rename = "UUID")]
+    uuid: String,
+    #[serde(rename = "Version")]
+    version: u64,
+}
+
+impl From<RawProvisioningProfile> for ProvisioningProfile {
+    fn from(raw: RawProvisioningProfile) -> Self {
+        // Keep only the entitlements whose value fits the typed field.
+        let fitting = raw
+            .entitlements
+            .iter()
+            .filter(|(key, value)| {
+                let mut single = Dictionary::new();
+                single.insert(key.to_string(), (*value).clone());
+                plist::from_value::<Entitlements>(&Value::Dictionary(single)).is_ok()
+            })
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        let entitlements = plist::from_value(&Value::Dictionary(fitting)).unwrap_or_default();
+        Self {
+            app_id_name: raw.app_id_name,
+            application_identifier_prefix: raw.application_identifier_prefix,
+            creation_date: raw.creation_date,
+            platform: raw.platform,
+            is_xcode_managed: raw.is_xcode_managed,
+            developer_certificates: raw.developer_certificates,
+            authorized_entitlements: raw.entitlements,
+            entitlements,
+            expiration_date: raw.expiration_date,
+            name: raw.name,
+            provisioned_devices: raw.provisioned_devices,
+            provisions_all_devices: raw.provisions_all_devices,
+            team_identifier: raw.team_identifier,
+            team_name: raw.team_name,
+            time_to_live: raw.time_to_live,
+            uuid: raw.uuid,
+            version: raw.version,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entitlements::prelude::*;
+
+    const PROFILE_PLIST: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<key>AppIDName</key>
+	<string>Example</string>
+	<key>ApplicationIdentifierPrefix</key>
+	<array>
+		<string>ABCDE12345</string>
+	</array>
+	<key>CreationDate</key>
+	<date>2024-01-01T00:00:00Z</date>
+	<key>Platform</key>
+	<array>
+		<string>iOS</string>
+	</array>
+	<key>DeveloperCertificates</key>
+	<array>
+		<data>MIIB</data>
+	</array>
+	<key>Entitlements</key>
+	<dict>
+		<key>application-identifier</key>
+		<string>ABCDE12345.com.example.app</string>
+		<key>aps-environment</key>
+		<string>development</string>
+		<key>com.apple.developer.associated-domains</key>
+		<string>*</string>
+		<key>keychain-access-groups</key>
+		<array>
+			<string>ABCDE12345.*</string>
+		</array>
+	</dict>
+	<key>ExpirationDate</key>
+	<date>2025-01-01T00:00:00Z</date>
+	<key>Name</key>
+	<string>Example Development</string>
+	<key>ProvisionedDevices</key>
+	<array>
+		<string>00008030-001A2B3C4D5E6F70</string>
+	</array>
+	<key>TeamIdentifier</key>
+	<array>
+		<string>ABCDE12345</string>
+	</array>
+	<key>TeamName</key>
+	<string>Example Team</string>
+	<key>TimeToLive</key>
+	<integer>366</integer>
+	<key>UUID</key>
+	<string>6b8e5f6e-2b1f-4a8e-9a57-0c2b6f0e8d11</string>
+	<key>Version</key>
+	<integer>1</integer>
+</dict>
+</plist>"#;
+
+    fn tlv(tag: u8, contents: &[u8]) -> Vec<u8> {
+        let mut encoded = vec![tag];
+        match contents.len() {
+            len if len < 0x80 => encoded.push(len as u8),
+            len => {
+                encoded.extend([0x82, (len >> 8) as u8, len as u8]);
+            }
+        }
+        encoded.extend_from_slice(contents);
+        encoded
+    }
+
+    /// Wraps the plist in a CMS envelope, using the indefinite-length BER encoding and
+    /// a segmented octet string like Apple's profiles do.
+    fn envelope(plist: &[u8]) -> Vec<u8> {
+        let (first, second) = plist.split_at(plist.len() / 2);
+        let mut octets = vec![0x24, 0x80];
+        octets.extend(tlv(der::OCTET_STRING, first));
+        octets.extend(tlv(der::OCTET_STRING, second));
+        octets.extend([0, 0]);
+        let data_oid = [0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x07, 0x01];
+        let mut encapsulated = tlv(der::OBJECT_IDENTIFIER, &data_oid);
+        encapsulated.extend(tlv(der::CONTEXT_0, &octets));
+        let mut signed_data = tlv(der::INTEGER, &[1]);
+        signed_data.extend(tlv(der::SET, &[]));
+        signed_data.extend(tlv(der::SEQUENCE, &encapsulated));
+        signed_data.extend(tlv(der::SET, &[]));
+        let mut content_info = vec![der::SEQUENCE, 0x80];
+        content_info.extend(tlv(der::OBJECT_IDENTIFIER, SIGNED_DATA_OID));
+        content_info.extend([der::CONTEXT_0, 0x80]);
+        content_info.extend(tlv(der::SEQUENCE, &signed_data));
+        content_info.extend([0, 0, 0, 0]);
+        content_info
+    }
+
+    #[test]
+    fn parses_profile_from_cms_envelope() {
+        let data = envelope(PROFILE_PLIST.as_bytes());
+        assert_eq!(
+            ProvisioningProfile::extract_plist(&data).unwrap(),
+            PROFILE_PLIST.as_bytes()
+        );
+        let profile = ProvisioningProfile::from_bytes(&data).unwrap();
+        assert_eq!(profile.app_id_name, "Example");
+        assert_eq!(profile.team_identifier, ["ABCDE12345"]);
+        assert_eq!(
+            profile.developer_certificates[0].as_ref(),
+            [0x30, 0x82, 0x01]
+        );
+        assert!(profile.provisions_device("00008030-001a2b3c4d5e6f70"));
+        assert!(profile.is_expired_at(SystemTime::now()));
+        assert_eq!(
+            profile.entitlements.push_notifications.aps_environment,
+            Some(APSEnvironment::Development)
+        );
+        assert_eq!(
+            profile.entitlements.security.keychain_access_groups,
+            Some(vec!["ABCDE12345.*".to_owned()])
+        );
+        assert_eq!(profile.entitlements.networking.associated_domains, None);
+        assert_eq!(
+            profile
+                .authorized_entitlements
+                .get("com.apple.developer.associated-domains")
+                .and_then(Value::as_string),
+            Some("*")
+        );
+    }
+
+    #[test]
+    fn rejects_other_content_types() {
+        let data = tlv(der::SEQUENCE, &tlv(der::OBJECT_IDENTIFIER, &[0x2a]));
+        assert!(matches!(
+            ProvisioningProfile::from_bytes(&data),
+            Err(Error::InvalidDer(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_malformed_lengths() {
+        let huge = [0x30, 0x88, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
+        assert!(matches!(
+            ProvisioningProfile::from_bytes(&huge),
+            Err(Error::InvalidDer(_))
+        ));
+        let nested = [0x30, 0x80].repeat(100_000);
+        assert!(matches!(
+            ProvisioningProfile::from_bytes(&nested),
+            Err(Error::InvalidDer(_))
+        ));
+    }
+
+    #[test]
+    fn reports_uncovered_entitlements() {
+        let profile = ProvisioningProfile::from_bytes(&envelope(PROFILE_PLIST.as_bytes())).unwrap();
+        let mut entitlements = Entitlements::default();
+        entitlements.push_notifications.aps_environment = Some(APSEnvironment::Production);
+        entitlements.security.keychain_access_groups = Some(vec![
+            "ABCDE12345.com.example.shared".to_owned(),
+            "OTHERTEAM1.com.example.shared".to_owned(),
+        ]);
+        entitlements.security.security_smartcard = Some(true);
+        entitlements.networking.associated_domains =
+            Some(vec!["applinks:example.com".parse().unwrap()]);
+        entitlements.games.game_center = Some(true);
+        entitlements.health.healthkit = Some(false);
+        let uncovered = profile.check_entitlements(&entitlements).unwrap();
+        let keys: Vec<_> = uncovered
+            .iter()
+            .map(|entitlement| (entitlement.key.as_str(), &entitlement.requested))
+            .collect();
+        assert_eq!(
+            keys,
+            [
+                ("com.apple.developer.game-center", &Value::Boolean(true)),
+                ("aps-environment", &Value::from("production")),
+                (
+                    "keychain-access-groups",
+                    &Value::from("OTHERTEAM1.com.example.shared")
+                ),
+            ]
+        );
+        assert_eq!(uncovered[0].authorized, None);
+        assert_eq!(uncovered[1].authorized, Some(Value::from("development")));
+    }
+
+    #[test]
+    fn matches_authorized_arrays() {
+        let mut authorized = Dictionary::new();
+        authorized.insert(
+            "com.apple.developer.icloud-container-environment".to_owned(),
+            Value::Array(vec!["Development".into(), "Production".into()]),
+        );
+        authorized.insert(
+            "com.apple.developer.icloud-container-identifiers".to_owned(),
+            Value::from("*"),
+        );
+        let mut requested = Dictionary::new();
+        requested.insert(
+            "com.apple.developer.icloud-container-environment".to_owned(),
+            Value::from("Production"),
+        );
+        requested.insert(
+            "com.apple.developer.icloud-container-identifiers".to_owned(),
+            Value::Array(vec!["iCloud.com.example.app".into()]),
+        );
+        assert!(uncovered_entitlements(&requested, &authorized).is_empty());
+    }
+
+    #[test]
+    fn requires_authorized_app_groups() {
+        let mut requested = Dictionary::new();
+        requested.insert(
+            "com.apple.security.app-sandbox".to_owned(),
+            Value::Boolean(true),
+        );
+        requested.insert(
+            "com.apple.security.application-groups".to_owned(),
+            Value::Array(vec!["ABCDE12345.com.example.shared".into()]),
+        );
+        let uncovered = uncovered_entitlements(&requested, &Dictionary::new());
+        assert_eq!(
+            uncovered,
+            [UncoveredEntitlement {
+                key: "com.apple.security.application-groups".to_owned(),
+                requested: Value::Array(vec!["ABCDE12345.com.example.shared".into()]),
+                authorized: None,
+            }]
+        );
+    }
+}
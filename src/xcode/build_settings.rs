@@ -0,0 +1,381 @@
+use crate::error::Result;
+use plist::Value;
+use serde::{de::DeserializeOwned, Serialize};
+use std::collections::BTreeMap;
+
+/// Xcode build settings, like `PRODUCT_BUNDLE_IDENTIFIER` or `MARKETING_VERSION`, that
+/// expand the `$(NAME)` references of property lists.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BuildSettings {
+    values: BTreeMap<String, String>,
+}
+
+/// The result of expanding build setting references.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Expansion<T> {
+    /// The expanded value.
+    pub value: T,
+    /// The names of the referenced build settings that aren't defined, in the order
+    /// they were first referenced. Their references are kept as they are.
+    pub unresolved: Vec<String>,
+}
+
+impl BuildSettings {
+    /// Creates an empty set of build settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the unexpanded value of a build setting.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(String::as_str)
+    }
+
+    /// Sets the unexpanded value of a build setting, which can reference other build
+    /// settings.
+    pub fn set(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        self.values.insert(name.into(), value.into());
+    }
+
+    /// Removes a build setting and returns its value.
+    pub fn remove(&mut self, name: &str) -> Option<String> {
+        self.values.remove(name)
+    }
+
+    /// Returns the build settings sorted by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.values
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    /// Returns the number of build settings.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns whether there are no build settings.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Expands the build setting references of a string.
+    ///
+    /// References are written `$(NAME)`, `${NAME}` or `$NAME`. Names can contain
+    /// references themselves, like `$(VERSION_$(CONFIGURATION))`, values are expanded
+    /// recursively, and a reference can be followed by modifiers, like
+    /// `$(PRODUCT_NAME:rfc1034identifier)`. The supported modifiers are
+    /// `rfc1034identifier`, `c99extidentifier`, `identifier`, `lower`, `upper`,
+    /// `quote`, `base`, `file`, `suffix` and `default=<value>`.
+    pub fn expand_str(&self, value: &str) -> Expansion<String> {
+        let mut expander = Expander {
+            settings: self,
+            stack: Vec::new(),
+            unresolved: Vec::new(),
+        };
+        let value = expander.expand(value);
+        Expansion {
+            value,
+            unresolved: expander.unresolved,
+        }
+    }
+
+    /// Expands the build setting references of every string in a property list value.
+    pub fn expand_value(&self, value: &Value) -> Expansion<Value> {
+        let mut expander = Expander {
+            settings: self,
+            stack: Vec::new(),
+            unresolved: Vec::new(),
+        };
+        let mut value = value.clone();
+        expander.expand_value(&mut value);
+        Expansion {
+            value,
+            unresolved: expander.unresolved,
+        }
+    }
+
+    /// Expands the build setting references of every string in a serializable value,
+    /// like an `InfoPlist` or `Entitlements` template.
+    pub fn expand<T: Serialize + DeserializeOwned>(&self, value: &T) -> Result<Expansion<T>> {
+        let expansion = self.expand_value(&plist::to_value(value)?);
+        Ok(Expansion {
+            value: plist::from_value(&expansion.value)?,
+            unresolved: expansion.unresolved,
+        })
+    }
+}
+
+impl<K: Into<String>, V: Into<String>> FromIterator<(K, V)> for BuildSettings {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut settings = Self::new();
+        settings.extend(iter);
+        settings
+    }
+}
+
+impl<K: Into<String>, V: Into<String>> Extend<(K, V)> for BuildSettings {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (name, value) in iter {
+            self.set(name, value);
+        }
+    }
+}
+
+struct Expander<'a> {
+    settings: &'a BuildSettings,
+    /// The build settings being expanded, to stop at cyclic references.
+    stack: Vec<String>,
+    unresolved: Vec<String>,
+}
+
+impl Expander<'_> {
+    fn expand_value(&mut self, value: &mut Value) {
+        match value {
+            Value::String(string) => *string = self.expand(string),
+            Value::Array(array) => {
+                for value in array {
+                    self.expand_value(value);
+                }
+            }
+            Value::Dictionary(dictionary) => {
+                for (_, value) in dictionary.iter_mut() {
+                    self.expand_value(value);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn expand(&mut self, value: &str) -> String {
+        let mut expanded = String::with_capacity(value.len());
+        let mut rest = value;
+        while let Some(start) = rest.find('$') {
+            expanded.push_str(&rest[..start]);
+            let reference = &rest[start..];
+            match split_reference(reference) {
+                Some((inner, len)) => {
+                    expanded.push_str(&self.resolve(inner, &reference[..len]));
+                    rest = &reference[len..];
+                }
+                None => {
+                    expanded.push('$');
+                    rest = &reference[1..];
+                }
+            }
+        }
+        expanded.push_str(rest);
+        expanded
+    }
+
+    /// Resolves the inside of a reference, like `PRODUCT_NAME:lower`, or returns the
+    /// reference as it is when the build setting isn't defined.
+    fn resolve(&mut self, inner: &str, reference: &str) -> String {
+        let inner = self.expand(inner);
+        let mut parts = inner.split(':');
+        let name = parts.next().unwrap_or_default();
+        let modifiers: Vec<&str> = parts.collect();
+        let default = modifiers
+            .iter()
+            .find_map(|modifier| modifier.strip_prefix("default="));
+        let mut value = match self.settings.get(name) {
+            Some(_) if self.stack.iter().any(|entered| entered == name) => {
+                self.report(name);
+                return reference.to_owned();
+            }
+            Some(value) => {
+                self.stack.push(name.to_owned());
+                let value = self.expand(value);
+                self.stack.pop();
+                value
+            }
+            None => match default {
+                Some(default) => default.to_owned(),
+                None => {
+                    self.report(name);
+                    return reference.to_owned();
+                }
+            },
+        };
+        for modifier in modifiers {
+            value = match modifier {
+                "rfc1034identifier" => value
+                    .chars()
+                    .map(|c| match c {
+                        c if c.is_ascii_alphanumeric() || c == '-' || c == '.' => c,
+                        _ => '-',
+                    })
+                    .collect(),
+                "c99extidentifier" | "identifier" => {
+                    let extended = modifier == "c99extidentifier";
+                    let mut identifier: String = value
+                        .chars()
+                        .map(|c| match c {
+                            c if c.is_ascii_alphanumeric() || c == '_' => c,
+                            c if extended && !c.is_ascii() && c.is_alphanumeric() => c,
+                            _ => '_',
+                        })
+                        .collect();
+                    if identifier.starts_with(|c: char| c.is_ascii_digit()) {
+                        identifier.insert(0, '_');
+                    }
+                    identifier
+                }
+                "lower" => value.to_lowercase(),
+                "upper" => value.to_uppercase(),
+                "quote" => {
+                    let mut quoted = String::with_capacity(value.len());
+                    for c in value.chars() {
+                        if c.is_whitespace() || matches!(c, '\\' | '"' | '\'') {
+                            quoted.push('\\');
+                        }
+                        quoted.push(c);
+                    }
+                    quoted
+                }
+                "file" => value.rsplit('/').next().unwrap_or_default().to_owned(),
+                "base" => {
+                    let file = value.rsplit('/').next().unwrap_or_default();
+                    match file.rfind('.') {
+                        Some(index) if index > 0 => file[..index].to_owned(),
+                        _ => file.to_owned(),
+                    }
+                }
+                "suffix" => {
+                    let file = value.rsplit('/').next().unwrap_or_default();
+                    match file.rfind('.') {
+                        Some(index) if index > 0 => file[index..].to_owned(),
+                        _ => String::new(),
+                    }
+                }
+                modifier if modifier.starts_with("default=") => value,
+                _ => {
+                    self.report(&format!("{}:{}", name, modifier));
+                    return reference.to_owned();
+                }
+            };
+        }
+        value
+    }
+
+    fn report(&mut self, name: &str) {
+        if !self.unresolved.iter().any(|unresolved| unresolved == name) {
+            self.unresolved.push(name.to_owned());
+        }
+    }
+}
+
+/// Splits a string starting with `$` into the inside of the reference it starts with
+/// and the length of the reference.
+fn split_reference(value: &str) -> Option<(&str, usize)> {
+    let after = &value[1..];
+    let open = after.chars().next()?;
+    let close = match open {
+        '(' => ')',
+        '{' => '}',
+        c if c.is_ascii_alphabetic() || c == '_' => {
+            let len = after
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                .unwrap_or(after.len());
+            return Some((&after[..len], len + 1));
+        }
+        _ => return None,
+    };
+    let mut depth = 0;
+    for (index, c) in after.char_indices() {
+        if c == open {
+            depth += 1;
+        } else if c == close {
+            depth -= 1;
+            if depth == 0 {
+                return Some((&after[1..index], index + 2));
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    fn settings() -> BuildSettings {
+        [
+            ("PRODUCT_NAME", "My App"),
+            (
+                "PRODUCT_BUNDLE_IDENTIFIER",
+                "com.example.$(PRODUCT_NAME:rfc1034identifier)",
+            ),
+            ("EXECUTABLE_NAME", "$(PRODUCT_NAME)"),
+            ("CONFIGURATION", "Release"),
+            ("VERSION_Release", "1.2"),
+            ("CYCLE", "$(CYCLE)"),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn expands_strings() {
+        let settings = settings();
+        for (value, expected) in [
+            ("$(PRODUCT_BUNDLE_IDENTIFIER)", "com.example.My-App"),
+            ("${EXECUTABLE_NAME}.app", "My App.app"),
+            ("$EXECUTABLE_NAME-1", "My App-1"),
+            ("$(VERSION_$(CONFIGURATION))", "1.2"),
+            ("$(PRODUCT_NAME:c99extidentifier)", "My_App"),
+            ("$(PRODUCT_NAME:lower:rfc1034identifier)", "my-app"),
+            ("$(PRODUCT_NAME:quote)", "My\\ App"),
+            ("$(MISSING:default=en)", "en"),
+            ("/tmp/$(CONFIGURATION:lower).tar.gz", "/tmp/release.tar.gz"),
+            ("$5 and $(", "$5 and $("),
+        ] {
+            let expansion = settings.expand_str(value);
+            assert_eq!(expansion.value, expected, "{}", value);
+            assert!(expansion.unresolved.is_empty(), "{}", value);
+        }
+
+        let expansion = settings.expand_str("$(MISSING).$(CYCLE).$(PRODUCT_NAME:nope).$(MISSING)");
+        assert_eq!(
+            expansion.value,
+            "$(MISSING).$(CYCLE).$(PRODUCT_NAME:nope).$(MISSING)"
+        );
+        assert_eq!(
+            expansion.unresolved,
+            ["MISSING", "CYCLE", "PRODUCT_NAME:nope"]
+        );
+    }
+
+    #[test]
+    fn expands_info_plist() {
+        let mut settings = settings();
+        settings.set("DEVELOPMENT_LANGUAGE", "en");
+        let info_plist = InfoPlist {
+            identification: Identification {
+                bundle_identifier: "$(PRODUCT_BUNDLE_IDENTIFIER)".to_owned(),
+                ..Default::default()
+            },
+            localization: Localization {
+                bundle_development_region: Some("$(DEVELOPMENT_LANGUAGE)".to_owned()),
+                ..Default::default()
+            },
+            bundle_version: BundleVersion {
+                bundle_short_version_string: Some("$(MARKETING_VERSION)".to_owned()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let expansion = settings.expand(&info_plist).unwrap();
+        let info_plist = expansion.value;
+        assert_eq!(
+            info_plist.identification.bundle_identifier,
+            "com.example.My-App"
+        );
+        assert_eq!(
+            info_plist.localization.bundle_development_region.as_deref(),
+            Some("en")
+        );
+        assert_eq!(expansion.unresolved, ["MARKETING_VERSION"]);
+    }
+}
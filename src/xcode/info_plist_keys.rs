@@ -0,0 +1,343 @@
+use super::build_settings::BuildSettings;
+use crate::{error::Result, info_plist::InfoPlist};
+use plist::{Dictionary, Value};
+
+/// The prefix of the build settings that set keys of a generated information property
+/// list, like `INFOPLIST_KEY_CFBundleDisplayName`.
+pub const INFOPLIST_KEY_PREFIX: &str = "INFOPLIST_KEY_";
+
+/// The keys of a generated information property list that come from dedicated build
+/// settings rather than `INFOPLIST_KEY_` settings.
+const DEDICATED_SETTINGS: [(&str, &str); 8] = [
+    ("CFBundleDevelopmentRegion", "DEVELOPMENT_LANGUAGE"),
+    ("CFBundleExecutable", "EXECUTABLE_NAME"),
+    ("CFBundleIdentifier", "PRODUCT_BUNDLE_IDENTIFIER"),
+    ("CFBundleName", "PRODUCT_NAME"),
+    ("CFBundlePackageType", "PRODUCT_BUNDLE_PACKAGE_TYPE"),
+    ("CFBundleShortVersionString", "MARKETING_VERSION"),
+    ("CFBundleVersion", "CURRENT_PROJECT_VERSION"),
+    ("UIDeviceFamily", "TARGETED_DEVICE_FAMILY"),
+];
+
+/// The result of converting between build settings and an information property list.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Conversion<T> {
+    /// The converted value.
+    pub value: T,
+    /// The build settings or information property list keys that couldn't be
+    /// converted, because this crate doesn't model them or because their value has no
+    /// equivalent on the other side.
+    pub unsupported: Vec<String>,
+}
+
+impl BuildSettings {
+    /// Generates the information property list of a target built with
+    /// `GENERATE_INFOPLIST_FILE = YES`, like Xcode does.
+    ///
+    /// `INFOPLIST_KEY_<key>` settings set `<key>`, with `_iPad` and `_iPhone` suffixes
+    /// becoming `~ipad` and `~iphone`. `YES` and `NO` become booleans and lists of words
+    /// become arrays when the key expects them. `INFOPLIST_KEY_UILaunchScreen_Generation`
+    /// and `INFOPLIST_KEY_UIApplicationSceneManifest_Generation` generate the default
+    /// launch screen and scene manifest. The bundle keys come from their dedicated
+    /// settings, like `PRODUCT_BUNDLE_IDENTIFIER`. Values are expanded first.
+    pub fn generate_info_plist(&self) -> Result<Conversion<InfoPlist>> {
+        let mut dictionary = Dictionary::new();
+        let mut unsupported = Vec::new();
+        dictionary.insert("CFBundleInfoDictionaryVersion".to_owned(), "6.0".into());
+        // `InfoPlist` requires a bundle identifier, like its `Default` implementation.
+        dictionary.insert("CFBundleIdentifier".to_owned(), "".into());
+        for (key, setting) in DEDICATED_SETTINGS {
+            if let Some(value) = self.get(setting) {
+                let value = self.expand_str(value).value;
+                let value = match key {
+                    "UIDeviceFamily" => Value::Array(
+                        value
+                            .split(',')
+                            .filter_map(|family| family.trim().parse::<u64>().ok())
+                            .map(Value::from)
+                            .collect(),
+                    ),
+                    _ => value.into(),
+                };
+                dictionary.insert(key.to_owned(), value);
+            }
+        }
+        for (setting, value) in self.iter() {
+            let Some(key) = setting.strip_prefix(INFOPLIST_KEY_PREFIX) else {
+                continue;
+            };
+            let value = self.expand_str(value).value;
+            let (key, value) = match key {
+                "UILaunchScreen_Generation" | "UIApplicationSceneManifest_Generation" => {
+                    if !is_yes(&value) {
+                        continue;
+                    }
+                    let key = key.trim_end_matches("_Generation");
+                    let mut generated = Dictionary::new();
+                    if key == "UIApplicationSceneManifest" {
+                        generated.insert(
+                            "UIApplicationSupportsMultipleScenes".to_owned(),
+                            true.into(),
+                        );
+                    }
+                    (key.to_owned(), Value::Dictionary(generated))
+                }
+                _ => {
+                    let key = match key.rsplit_once('_') {
+                        Some((key, "iPad")) => format!("{}~ipad", key),
+                        Some((key, "iPhone")) => format!("{}~iphone", key),
+                        _ => key.to_owned(),
+                    };
+                    match candidates(&value)
+                        .into_iter()
+                        .find(|candidate| is_modelled(&key, candidate))
+                    {
+                        Some(value) => (key, value),
+                        None => {
+                            unsupported.push(setting.to_owned());
+                            continue;
+                        }
+                    }
+                }
+            };
+            dictionary.insert(key, value);
+        }
+        Ok(Conversion {
+            value: plist::from_value(&Value::Dictionary(dictionary))?,
+            unsupported,
+        })
+    }
+
+    /// Returns the build settings that make Xcode generate `info_plist`, to be written
+    /// to an `.xcconfig` file, including `GENERATE_INFOPLIST_FILE = YES`.
+    ///
+    /// Keys whose value is a dictionary or an array of values with spaces can't be
+    /// written as build settings and are reported as unsupported, except for the
+    /// default launch screen and scene manifest.
+    pub fn from_info_plist(info_plist: &InfoPlist) -> Result<Conversion<Self>> {
+        let mut settings = Self::new();
+        let mut unsupported = Vec::new();
+        settings.set("GENERATE_INFOPLIST_FILE", "YES");
+        let Value::Dictionary(dictionary) = plist::to_value(info_plist)? else {
+            return Ok(Conversion {
+                value: settings,
+                unsupported,
+            });
+        };
+        for (key, value) in dictionary {
+            if key == "CFBundleInfoDictionaryVersion" {
+                continue;
+            }
+            if let Some((_, setting)) = DEDICATED_SETTINGS.iter().find(|(name, _)| *name == key) {
+                let value = match (&value, key.as_str()) {
+                    (Value::Array(families), "UIDeviceFamily") => families
+                        .iter()
+                        .filter_map(Value::as_unsigned_integer)
+                        .map(|family| family.to_string())
+                        .collect::<Vec<_>>()
+                        .join(","),
+                    _ => setting_value(&value).unwrap_or_default(),
+                };
+                settings.set(*setting, value);
+                continue;
+            }
+            let setting = match key.rsplit_once('~') {
+                Some((key, "ipad")) => format!("{}{}_iPad", INFOPLIST_KEY_PREFIX, key),
+                Some((key, "iphone")) => format!("{}{}_iPhone", INFOPLIST_KEY_PREFIX, key),
+                _ => format!("{}{}", INFOPLIST_KEY_PREFIX, key),
+            };
+            let generated = match key.as_str() {
+                "UILaunchScreen" => Some(Dictionary::new()),
+                "UIApplicationSceneManifest" => {
+                    let mut generated = Dictionary::new();
+                    generated.insert(
+                        "UIApplicationSupportsMultipleScenes".to_owned(),
+                        true.into(),
+                    );
+                    Some(generated)
+                }
+                _ => None,
+            };
+            match (generated, setting_value(&value)) {
+                (Some(generated), _) if value.as_dictionary() == Some(&generated) => {
+                    settings.set(format!("{}_Generation", setting), "YES");
+                }
+                (None, Some(value)) => settings.set(setting, value),
+                _ => unsupported.push(key),
+            }
+        }
+        Ok(Conversion {
+            value: settings,
+            unsupported,
+        })
+    }
+}
+
+fn is_yes(value: &str) -> bool {
+    matches!(value, "YES" | "yes" | "true")
+}
+
+/// Returns the property list values a build setting value can stand for, in the order
+/// they are tried.
+fn candidates(value: &str) -> Vec<Value> {
+    let mut candidates = Vec::new();
+    match value {
+        "YES" | "yes" | "true" => candidates.push(true.into()),
+        "NO" | "no" | "false" => candidates.push(false.into()),
+        _ => {}
+    }
+    if let Ok(integer) = value.parse::<i64>() {
+        candidates.push(integer.into());
+    }
+    candidates.push(value.into());
+    candidates.push(Value::Array(
+        value.split_whitespace().map(Value::from).collect(),
+    ));
+    candidates
+}
+
+/// Returns whether `InfoPlist` models `key` with a value of the type of `value`.
+fn is_modelled(key: &str, value: &Value) -> bool {
+    let mut dictionary = Dictionary::new();
+    dictionary.insert("CFBundleIdentifier".to_owned(), "".into());
+    dictionary.insert(key.to_owned(), value.clone());
+    plist::from_value::<InfoPlist>(&Value::Dictionary(dictionary))
+        .and_then(|info_plist| plist::to_value(&info_plist))
+        .is_ok_and(|value| {
+            value
+                .as_dictionary()
+                .is_some_and(|dictionary| dictionary.contains_key(key))
+        })
+}
+
+/// Returns the build setting value of a property list value, if it has one.
+fn setting_value(value: &Value) -> Option<String> {
+    match value {
+        Value::String(string) => Some(string.clone()),
+        Value::Boolean(true) => Some("YES".to_owned()),
+        Value::Boolean(false) => Some("NO".to_owned()),
+        Value::Integer(integer) => Some(integer.to_string()),
+        Value::Real(real) => Some(real.to_string()),
+        Value::Array(array) => {
+            let words = array
+                .iter()
+                .map(|value| match value {
+                    Value::String(string) if !string.contains(char::is_whitespace) => {
+                        Some(string.clone())
+                    }
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()?;
+            Some(words.join(" "))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn generates_info_plist_from_build_settings() {
+        let settings = BuildSettings::from_iter([
+            ("PRODUCT_NAME", "Example"),
+            ("PRODUCT_BUNDLE_IDENTIFIER", "com.example.$(PRODUCT_NAME)"),
+            ("MARKETING_VERSION", "1.0"),
+            ("CURRENT_PROJECT_VERSION", "7"),
+            ("TARGETED_DEVICE_FAMILY", "1,2"),
+            ("INFOPLIST_KEY_CFBundleDisplayName", "$(PRODUCT_NAME)"),
+            ("INFOPLIST_KEY_NSCameraUsageDescription", "Scan codes"),
+            ("INFOPLIST_KEY_UIRequiresFullScreen", "YES"),
+            ("INFOPLIST_KEY_UILaunchScreen_Generation", "YES"),
+            ("INFOPLIST_KEY_UIApplicationSceneManifest_Generation", "YES"),
+            (
+                "INFOPLIST_KEY_UISupportedInterfaceOrientations",
+                "UIInterfaceOrientationPortrait UIInterfaceOrientationLandscapeLeft",
+            ),
+            (
+                "INFOPLIST_KEY_UISupportedInterfaceOrientations_iPad",
+                "UIInterfaceOrientationPortrait",
+            ),
+            ("INFOPLIST_KEY_CFBundleUnknown", "1"),
+        ]);
+        let conversion = settings.generate_info_plist().unwrap();
+        let info_plist = &conversion.value;
+        assert_eq!(
+            info_plist.identification.bundle_identifier,
+            "com.example.Example"
+        );
+        assert_eq!(
+            info_plist.naming.bundle_display_name.as_deref(),
+            Some("Example")
+        );
+        assert_eq!(
+            info_plist.bundle_version.bundle_version.as_deref(),
+            Some("7")
+        );
+        assert_eq!(
+            info_plist
+                .camera_and_microphone
+                .camera_usage_description
+                .as_deref(),
+            Some("Scan codes")
+        );
+        assert_eq!(info_plist.styling.requires_full_screen, Some(true));
+        assert_eq!(
+            info_plist.launch_interface.launch_screen,
+            Some(LaunchScreen::default())
+        );
+        assert_eq!(
+            info_plist
+                .main_user_interface
+                .application_scene_manifest
+                .as_ref()
+                .and_then(|manifest| manifest.enable_multiple_windows),
+            Some(true)
+        );
+        assert_eq!(
+            info_plist.orientation.supported_interface_orientations,
+            Some(vec![
+                InterfaceOrientation::Portrait,
+                InterfaceOrientation::LandscapeLeft
+            ])
+        );
+        assert_eq!(
+            info_plist.orientation.supported_interface_orientations_ipad,
+            Some(vec![InterfaceOrientation::Portrait])
+        );
+        assert_eq!(
+            info_plist.background_execution.ui_device_family,
+            Some(vec![1, 2])
+        );
+        assert_eq!(conversion.unsupported, ["INFOPLIST_KEY_CFBundleUnknown"]);
+
+        let conversion = BuildSettings::from_info_plist(info_plist).unwrap();
+        let settings = conversion.value;
+        assert!(conversion.unsupported.is_empty());
+        assert_eq!(settings.get("GENERATE_INFOPLIST_FILE"), Some("YES"));
+        assert_eq!(
+            settings.get("PRODUCT_BUNDLE_IDENTIFIER"),
+            Some("com.example.Example")
+        );
+        assert_eq!(settings.get("TARGETED_DEVICE_FAMILY"), Some("1,2"));
+        assert_eq!(
+            settings.get("INFOPLIST_KEY_UIRequiresFullScreen"),
+            Some("YES")
+        );
+        assert_eq!(
+            settings.get("INFOPLIST_KEY_UILaunchScreen_Generation"),
+            Some("YES")
+        );
+        assert_eq!(
+            settings.get("INFOPLIST_KEY_UISupportedInterfaceOrientations"),
+            Some("UIInterfaceOrientationPortrait UIInterfaceOrientationLandscapeLeft")
+        );
+        assert_eq!(
+            settings.get("INFOPLIST_KEY_UISupportedInterfaceOrientations_iPad"),
+            Some("UIInterfaceOrientationPortrait")
+        );
+        assert_eq!(settings.generate_info_plist().unwrap().value, *info_plist);
+    }
+}
@@ -0,0 +1,28 @@
+//! # Xcode.
+//!
+//! The build settings of Xcode projects that property list templates are expanded
+//! with.
+//!
+//! Info.plist and entitlements files in Xcode projects reference build settings, like
+//! `$(PRODUCT_BUNDLE_IDENTIFIER)`, that Xcode substitutes when it processes the files
+//! during a build. Build settings come from the project and from `.xcconfig` files,
+//! where they can depend on the SDK, the architecture and the build configuration.
+//! The targets of a project and the settings of their build configurations are read
+//! from its `project.pbxproj` file, and property lists can be written formatted like
+//! Xcode writes them.
+//!
+//! Official documentation: <https://developer.apple.com/documentation/xcode/build-settings-reference>
+
+pub mod build_settings;
+pub mod info_plist_keys;
+pub mod plist_writer;
+pub mod project;
+pub mod xcconfig;
+
+pub mod prelude {
+    pub use super::build_settings::*;
+    pub use super::info_plist_keys::*;
+    pub use super::plist_writer::*;
+    pub use super::project::*;
+    pub use super::xcconfig::*;
+}
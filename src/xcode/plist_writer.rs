@@ -0,0 +1,264 @@
+use crate::error::{Error, Result};
+use plist::Value;
+use serde::Serialize;
+use std::{fmt::Write, path::Path};
+
+const HEADER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+"#;
+
+/// The length of the lines of base64 encoded data, like Xcode writes them.
+const DATA_LINE_LEN: usize = 76;
+
+/// The order of the keys of written dictionaries.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum KeyOrder {
+    /// Keys sorted by their bytes, like Xcode sorts them.
+    #[default]
+    Alphabetical,
+    /// Keys in the order of the fields of the serialized struct, like `InfoPlist`.
+    FieldOrder,
+}
+
+/// Writes XML property lists, like `InfoPlist` and `Entitlements`, formatted like
+/// Xcode writes them, so that regenerated files don't differ from the ones Xcode
+/// edits.
+///
+/// Xcode indents with tabs, keeps the root dictionary unindented, writes empty
+/// containers as `<dict/>` and `<array/>`, wraps data in lines of 76 characters and
+/// ends the file with a newline.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct XcodePlistWriter {
+    /// The order of the keys of dictionaries.
+    pub key_order: KeyOrder,
+}
+
+impl XcodePlistWriter {
+    /// Creates a writer that orders the keys of dictionaries by `key_order`.
+    pub fn new(key_order: KeyOrder) -> Self {
+        Self { key_order }
+    }
+
+    /// Returns the XML property list of a value.
+    pub fn to_string<T: Serialize>(&self, value: &T) -> Result<String> {
+        let mut output = HEADER.to_owned();
+        self.write_value(&mut output, &plist::to_value(value)?, 0)?;
+        output.push_str("\n</plist>\n");
+        Ok(output)
+    }
+
+    /// Writes the XML property list of a value to `path`.
+    pub fn to_file<T: Serialize>(&self, path: impl AsRef<Path>, value: &T) -> Result<()> {
+        Ok(std::fs::write(path, self.to_string(value)?)?)
+    }
+
+    /// Writes a value whose opening tag is already indented by `depth` tabs.
+    fn write_value(&self, output: &mut String, value: &Value, depth: usize) -> Result<()> {
+        let indent = "\t".repeat(depth);
+        match value {
+            Value::Dictionary(dictionary) if dictionary.is_empty() => output.push_str("<dict/>"),
+            Value::Dictionary(dictionary) => {
+                let mut entries: Vec<_> = dictionary.iter().collect();
+                if self.key_order == KeyOrder::Alphabetical {
+                    entries.sort_by_key(|(key, _)| *key);
+                }
+                output.push_str("<dict>\n");
+                for (key, value) in entries {
+                    let _ = write!(
+                        output,
+                        "{}\t<key>{}</key>\n{}\t",
+                        indent,
+                        escape(key),
+                        indent
+                    );
+                    self.write_value(output, value, depth + 1)?;
+                    output.push('\n');
+                }
+                let _ = write!(output, "{}</dict>", indent);
+            }
+            Value::Array(array) if array.is_empty() => output.push_str("<array/>"),
+            Value::Array(array) => {
+                output.push_str("<array>\n");
+                for value in array {
+                    let _ = write!(output, "{}\t", indent);
+                    self.write_value(output, value, depth + 1)?;
+                    output.push('\n');
+                }
+                let _ = write!(output, "{}</array>", indent);
+            }
+            Value::Boolean(true) => output.push_str("<true/>"),
+            Value::Boolean(false) => output.push_str("<false/>"),
+            Value::Data(data) => {
+                output.push_str("<data>\n");
+                let encoded = base64(data);
+                for line in encoded.as_bytes().chunks(DATA_LINE_LEN) {
+                    let _ = writeln!(output, "{}{}", indent, String::from_utf8_lossy(line));
+                }
+                let _ = write!(output, "{}</data>", indent);
+            }
+            Value::Date(date) => {
+                let _ = write!(output, "<date>{}</date>", date.to_xml_format());
+            }
+            Value::Real(real) => {
+                let _ = write!(output, "<real>{}</real>", real);
+            }
+            Value::Integer(integer) => {
+                let _ = write!(output, "<integer>{}</integer>", integer);
+            }
+            Value::String(string) => {
+                let _ = write!(output, "<string>{}</string>", escape(string));
+            }
+            _ => {
+                return Err(Error::InvalidXmlPlist(
+                    "unsupported property list value".to_owned(),
+                ))
+            }
+        }
+        Ok(())
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let group = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for index in 0..4 {
+            if index <= chunk.len() {
+                let sextet = (group >> (18 - 6 * index)) & 0x3f;
+                encoded.push(ALPHABET[sextet as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entitlements::{games::Games, health::Health},
+        prelude::*,
+    };
+
+    fn info_plist() -> InfoPlist {
+        InfoPlist {
+            identification: Identification {
+                bundle_identifier: "com.example.app".to_owned(),
+                ..Default::default()
+            },
+            naming: Naming {
+                bundle_name: Some("Example & Co".to_owned()),
+                ..Default::default()
+            },
+            bundle_version: BundleVersion {
+                bundle_version: Some("1".to_owned()),
+                bundle_short_version_string: Some("1.0".to_owned()),
+                ..Default::default()
+            },
+            operating_system_version: OperatingSystemVersion {
+                requires_iphone_os: Some(true),
+                ..Default::default()
+            },
+            background_execution: BackgroundExecution {
+                ui_device_family: Some(vec![1, 2]),
+                ..Default::default()
+            },
+            url_schemes: UrlSchemes {
+                bundle_url_types: Some(vec![BundleUrlTypes {
+                    bundle_url_name: "com.example.app".to_owned(),
+                    bundle_url_schemes: Some(vec!["example".to_owned()]),
+                    ..Default::default()
+                }]),
+            },
+            ..Default::default()
+        }
+    }
+
+    fn entitlements() -> Entitlements {
+        Entitlements {
+            code_signing: CodeSigning {
+                application_identifier: Some("ABCDE12345.com.example.app".to_owned()),
+                team_identifier: Some("ABCDE12345".to_owned()),
+                get_task_allow: Some(true),
+                ..Default::default()
+            },
+            games: Games {
+                game_center: Some(true),
+            },
+            health: Health {
+                healthkit: Some(false),
+                healthkit_access: Some(vec![]),
+            },
+            ..Default::default()
+        }
+    }
+
+    // The golden files follow the format of the property lists Xcode saves, but weren't
+    // saved by Xcode itself.
+    #[test]
+    fn writes_like_xcode() {
+        let writer = XcodePlistWriter::default();
+        let written = writer.to_string(&info_plist()).unwrap();
+        assert_eq!(written, include_str!("golden/Info.plist"));
+        assert_eq!(
+            plist::from_bytes::<InfoPlist>(written.as_bytes()).unwrap(),
+            info_plist()
+        );
+        assert_eq!(
+            writer.to_string(&entitlements()).unwrap(),
+            include_str!("golden/App.entitlements")
+        );
+        assert_eq!(
+            XcodePlistWriter::new(KeyOrder::FieldOrder)
+                .to_string(&entitlements())
+                .unwrap(),
+            include_str!("golden/App.field-order.entitlements")
+        );
+
+        let mut dictionary = plist::Dictionary::new();
+        dictionary.insert("Data".to_owned(), Value::Data(vec![0; 60]));
+        dictionary.insert("Real".to_owned(), Value::Real(1.5));
+        dictionary.insert(
+            "Values".to_owned(),
+            Value::Array(vec![Value::Data(vec![1, 2, 3, 4]), Value::Real(-0.25)]),
+        );
+        let written = writer.to_string(&dictionary).unwrap();
+        assert_eq!(written, include_str!("golden/Values.plist"));
+        assert_eq!(
+            plist::from_bytes::<plist::Dictionary>(written.as_bytes()).unwrap(),
+            dictionary
+        );
+    }
+
+    #[test]
+    fn encodes_base64() {
+        // The test vectors of RFC 4648.
+        for (data, encoded) in [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ] {
+            assert_eq!(base64(data.as_bytes()), encoded);
+        }
+        assert_eq!(base64(&[0xfb, 0xff]), "+/8=");
+    }
+}
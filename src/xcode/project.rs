@@ -0,0 +1,509 @@
+use super::{
+    build_settings::{BuildSettings, Expansion},
+    xcconfig::{parse_setting, BuildConditions, XcConfig},
+};
+use crate::{
+    entitlements::Entitlements,
+    error::{Error, Result},
+    info_plist::InfoPlist,
+    openstep,
+};
+use plist::{Dictionary, Value};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+/// An Xcode project, `.xcodeproj`, read from its `project.pbxproj` file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct XcodeProject {
+    /// The path of the `.xcodeproj` directory.
+    pub path: PathBuf,
+    objects: Dictionary,
+    root_object: String,
+}
+
+/// A target of an Xcode project.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct XcodeTarget {
+    /// The name of the target.
+    pub name: String,
+    /// The name of the product, like `Example`.
+    pub product_name: Option<String>,
+    /// The type of the product, like `com.apple.product-type.application`.
+    pub product_type: Option<String>,
+    /// The names of the build configurations of the target.
+    pub configurations: Vec<String>,
+}
+
+/// The build settings of a target for one build configuration, and the property list
+/// files they reference.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TargetConfiguration {
+    /// The resolved build settings, with references left unexpanded.
+    pub settings: BuildSettings,
+    /// The Info.plist file, from `INFOPLIST_FILE`.
+    pub info_plist_file: Option<PathBuf>,
+    /// The entitlements file, from `CODE_SIGN_ENTITLEMENTS`.
+    pub entitlements_file: Option<PathBuf>,
+    /// The expanded `PRODUCT_BUNDLE_IDENTIFIER`.
+    pub bundle_identifier: Option<String>,
+    /// The expanded `MARKETING_VERSION`.
+    pub marketing_version: Option<String>,
+}
+
+impl XcodeProject {
+    /// Reads the project at `path`, either the `.xcodeproj` directory or its
+    /// `project.pbxproj` file.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let (path, pbxproj) = if path.is_dir() {
+            (path.to_owned(), path.join("project.pbxproj"))
+        } else {
+            let dir = path.parent().unwrap_or_else(|| Path::new(""));
+            (dir.to_owned(), path.to_owned())
+        };
+        Self::parse(&std::fs::read_to_string(pbxproj)?, path)
+    }
+
+    /// Parses the contents of a `project.pbxproj` file of the project at `path`.
+    pub fn parse(pbxproj: &str, path: impl Into<PathBuf>) -> Result<Self> {
+        let invalid = |reason: &str| Error::InvalidProject(reason.to_owned());
+        let mut root = match openstep::value_from_str(pbxproj)? {
+            Value::Dictionary(root) => root,
+            _ => return Err(invalid("the root of project.pbxproj is not a dictionary")),
+        };
+        let objects = match root.remove("objects") {
+            Some(Value::Dictionary(objects)) => objects,
+            _ => return Err(invalid("no objects in project.pbxproj")),
+        };
+        let root_object = root
+            .get("rootObject")
+            .and_then(Value::as_string)
+            .ok_or_else(|| invalid("no root object in project.pbxproj"))?
+            .to_owned();
+        Ok(Self {
+            path: path.into(),
+            objects,
+            root_object,
+        })
+    }
+
+    /// Returns the directory holding the `.xcodeproj`, `SRCROOT` in build settings.
+    pub fn project_dir(&self) -> &Path {
+        self.path.parent().unwrap_or_else(|| Path::new(""))
+    }
+
+    fn object(&self, id: &str) -> Result<&Dictionary> {
+        self.objects
+            .get(id)
+            .and_then(Value::as_dictionary)
+            .ok_or_else(|| Error::InvalidProject(format!("missing object {}", id)))
+    }
+
+    fn ids<'a>(object: &'a Dictionary, key: &str) -> impl Iterator<Item = &'a str> {
+        object
+            .get(key)
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_string)
+    }
+
+    fn string<'a>(object: &'a Dictionary, key: &str) -> Option<&'a str> {
+        object.get(key).and_then(Value::as_string)
+    }
+
+    fn root(&self) -> Result<&Dictionary> {
+        self.object(&self.root_object)
+    }
+
+    /// Returns the build configurations of a configuration list, by name.
+    fn configurations(&self, object: &Dictionary) -> Result<Vec<(String, &Dictionary)>> {
+        let list = Self::string(object, "buildConfigurationList")
+            .ok_or_else(|| Error::InvalidProject("missing build configuration list".to_owned()))?;
+        Self::ids(self.object(list)?, "buildConfigurations")
+            .map(|id| {
+                let configuration = self.object(id)?;
+                let name = Self::string(configuration, "name").unwrap_or_default();
+                Ok((name.to_owned(), configuration))
+            })
+            .collect()
+    }
+
+    /// Returns the targets of the project, in project order.
+    pub fn targets(&self) -> Result<Vec<XcodeTarget>> {
+        Self::ids(self.root()?, "targets")
+            .map(|id| {
+                let target = self.object(id)?;
+                Ok(XcodeTarget {
+                    name: Self::string(target, "name").unwrap_or_default().to_owned(),
+                    product_name: Self::string(target, "productName").map(str::to_owned),
+                    product_type: Self::string(target, "productType").map(str::to_owned),
+                    configurations: self
+                        .configurations(target)?
+                        .into_iter()
+                        .map(|(name, _)| name)
+                        .collect(),
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the names of the build configurations of the project.
+    pub fn configuration_names(&self) -> Result<Vec<String>> {
+        Ok(self
+            .configurations(self.root()?)?
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect())
+    }
+
+    /// Resolves the build settings of a target for a build configuration.
+    ///
+    /// Settings are layered like Xcode does: `SRCROOT`, `PROJECT_NAME`, `TARGET_NAME`,
+    /// `CONFIGURATION` and `PRODUCT_NAME` first, then the `.xcconfig` file and the
+    /// settings of the project configuration, then those of the target configuration.
+    pub fn target_configuration(
+        &self,
+        target: &str,
+        configuration: &str,
+        conditions: &BuildConditions,
+    ) -> Result<TargetConfiguration> {
+        let (target_name, target_object) = Self::ids(self.root()?, "targets")
+            .map(|id| self.object(id))
+            .find_map(|object| {
+                let object = object.ok()?;
+                let name = Self::string(object, "name")?;
+                (name == target).then_some((name, object))
+            })
+            .ok_or_else(|| Error::InvalidProject(format!("no target named {}", target)))?;
+        let conditions = BuildConditions {
+            config: Some(configuration.to_owned()),
+            ..conditions.clone()
+        };
+
+        let project_dir = self.project_dir();
+        let mut settings = BuildSettings::new();
+        let project_name = self.path.file_stem().unwrap_or_default().to_string_lossy();
+        for name in ["SRCROOT", "SOURCE_ROOT", "PROJECT_DIR"] {
+            settings.set(name, project_dir.to_string_lossy());
+        }
+        settings.set("PROJECT_NAME", project_name);
+        settings.set("TARGET_NAME", target_name);
+        settings.set("CONFIGURATION", configuration);
+        settings.set(
+            "PRODUCT_NAME",
+            Self::string(target_object, "productName").unwrap_or(target_name),
+        );
+        for object in [self.root()?, target_object] {
+            let Some((_, configuration)) = self
+                .configurations(object)?
+                .into_iter()
+                .find(|(name, _)| name == configuration)
+            else {
+                continue;
+            };
+            if let Some(reference) = Self::string(configuration, "baseConfigurationReference") {
+                let path = self.file_path(reference)?;
+                settings = XcConfig::open(path)?.resolve(&conditions, &settings);
+            }
+            if let Some(build_settings) = configuration
+                .get("buildSettings")
+                .and_then(Value::as_dictionary)
+            {
+                settings = build_settings_config(build_settings)?.resolve(&conditions, &settings);
+            }
+        }
+
+        let expand = |name: &str| {
+            settings
+                .get(name)
+                .map(|value| settings.expand_str(value).value)
+        };
+        let path = |name: &str| {
+            expand(name)
+                .filter(|path| !path.is_empty())
+                .map(|path| project_dir.join(path))
+        };
+        Ok(TargetConfiguration {
+            info_plist_file: path("INFOPLIST_FILE"),
+            entitlements_file: path("CODE_SIGN_ENTITLEMENTS"),
+            bundle_identifier: expand("PRODUCT_BUNDLE_IDENTIFIER"),
+            marketing_version: expand("MARKETING_VERSION"),
+            settings,
+        })
+    }
+
+    /// Returns the path of a file reference, following the groups that contain it.
+    fn file_path(&self, id: &str) -> Result<PathBuf> {
+        let parents: HashMap<&str, &str> = self
+            .objects
+            .iter()
+            .filter_map(|(parent, object)| Some((parent, object.as_dictionary()?)))
+            .flat_map(|(parent, object)| {
+                Self::ids(object, "children").map(move |child| (child, parent.as_str()))
+            })
+            .collect();
+        let mut components = Vec::new();
+        let mut visited = HashSet::new();
+        let mut id = id;
+        loop {
+            if !visited.insert(id) {
+                return Err(Error::InvalidProject(format!(
+                    "group {} contains itself",
+                    id
+                )));
+            }
+            let object = self.object(id)?;
+            if let Some(path) = Self::string(object, "path") {
+                components.push(path);
+            }
+            match Self::string(object, "sourceTree").unwrap_or("<group>") {
+                "<group>" => match parents.get(id) {
+                    Some(parent) => id = parent,
+                    None => break,
+                },
+                "SOURCE_ROOT" => break,
+                "<absolute>" => {
+                    return Ok(components.iter().rev().collect());
+                }
+                tree => {
+                    return Err(Error::InvalidProject(format!(
+                        "unsupported source tree {} of {}",
+                        tree, id
+                    )))
+                }
+            }
+        }
+        let mut path = self.project_dir().to_owned();
+        path.extend(components.iter().rev());
+        Ok(path)
+    }
+}
+
+/// Returns the build settings of a configuration as `.xcconfig` assignments.
+fn build_settings_config(build_settings: &Dictionary) -> Result<XcConfig> {
+    let settings = build_settings
+        .iter()
+        .map(|(name, value)| {
+            let value = match value {
+                // Xcode quotes the elements of list settings that contain whitespace.
+                Value::Array(values) => values
+                    .iter()
+                    .filter_map(Value::as_string)
+                    .map(|value| match value.contains(char::is_whitespace) {
+                        true => format!("\"{}\"", value),
+                        false => value.to_owned(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                value => value.as_string().unwrap_or_default().to_owned(),
+            };
+            parse_setting(name, &value)
+                .ok_or_else(|| Error::InvalidProject(format!("invalid build setting {}", name)))
+        })
+        .collect::<Result<_>>()?;
+    Ok(XcConfig { settings })
+}
+
+impl TargetConfiguration {
+    /// Reads the Info.plist file and expands its build setting references.
+    pub fn info_plist(&self) -> Result<Option<Expansion<InfoPlist>>> {
+        self.read(self.info_plist_file.as_deref())
+    }
+
+    /// Reads the entitlements file and expands its build setting references.
+    pub fn entitlements(&self) -> Result<Option<Expansion<Entitlements>>> {
+        self.read(self.entitlements_file.as_deref())
+    }
+
+    fn read<T: serde::Serialize + serde::de::DeserializeOwned + Default>(
+        &self,
+        path: Option<&Path>,
+    ) -> Result<Option<Expansion<T>>> {
+        let Some(path) = path else {
+            return Ok(None);
+        };
+        let value: T = openstep::read_file(path)?;
+        Ok(Some(self.settings.expand(&value)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    const PBXPROJ: &str = r#"// !$*UTF8*$!
+{
+	archiveVersion = 1;
+	classes = {
+	};
+	objectVersion = 56;
+	objects = {
+
+/* Begin PBXFileReference section */
+		F1 /* App.xcconfig */ = {isa = PBXFileReference; lastKnownFileType = text.xcconfig; path = App.xcconfig; sourceTree = "<group>"; };
+/* End PBXFileReference section */
+
+/* Begin PBXGroup section */
+		G0 = {isa = PBXGroup; children = (G1, ); sourceTree = "<group>"; };
+		G1 /* Config */ = {isa = PBXGroup; children = (F1 /* App.xcconfig */, ); path = Config; sourceTree = "<group>"; };
+/* End PBXGroup section */
+
+/* Begin PBXNativeTarget section */
+		T1 /* Example */ = {
+			isa = PBXNativeTarget;
+			buildConfigurationList = L2 /* Build configuration list for PBXNativeTarget "Example" */;
+			name = Example;
+			productName = Example;
+			productType = "com.apple.product-type.application";
+		};
+/* End PBXNativeTarget section */
+
+/* Begin PBXProject section */
+		P1 /* Project object */ = {
+			isa = PBXProject;
+			buildConfigurationList = L1;
+			mainGroup = G0;
+			targets = (
+				T1 /* Example */,
+			);
+		};
+/* End PBXProject section */
+
+/* Begin XCBuildConfiguration section */
+		C1 /* Debug */ = {isa = XCBuildConfiguration; buildSettings = { MARKETING_VERSION = 1.0; }; name = Debug; };
+		C2 /* Release */ = {isa = XCBuildConfiguration; buildSettings = { MARKETING_VERSION = 1.0; }; name = Release; };
+		C3 /* Debug */ = {
+			isa = XCBuildConfiguration;
+			baseConfigurationReference = F1 /* App.xcconfig */;
+			buildSettings = {
+				CODE_SIGN_ENTITLEMENTS = Example/Example.entitlements;
+				CURRENT_PROJECT_VERSION = 3;
+				INFOPLIST_FILE = "$(SRCROOT)/Example/Info.plist";
+				"PRODUCT_BUNDLE_IDENTIFIER[sdk=iphonesimulator*]" = "$(inherited).simulator";
+				OTHER_LDFLAGS = (
+					"$(inherited)",
+					"-ObjC",
+					"-L$(SRCROOT)/My Dir",
+				);
+			};
+			name = Debug;
+		};
+		C4 /* Release */ = {isa = XCBuildConfiguration; buildSettings = { MARKETING_VERSION = 2.0; }; name = Release; };
+/* End XCBuildConfiguration section */
+
+/* Begin XCConfigurationList section */
+		L1 = {isa = XCConfigurationList; buildConfigurations = (C1, C2, ); defaultConfigurationName = Release; };
+		L2 = {isa = XCConfigurationList; buildConfigurations = (C3, C4, ); defaultConfigurationName = Release; };
+/* End XCConfigurationList section */
+	};
+	rootObject = P1 /* Project object */;
+}
+"#;
+
+    #[test]
+    fn reads_target_configurations() {
+        let dir = tempfile::tempdir().unwrap();
+        let xcodeproj = dir.path().join("Example.xcodeproj");
+        std::fs::create_dir_all(&xcodeproj).unwrap();
+        std::fs::write(xcodeproj.join("project.pbxproj"), PBXPROJ).unwrap();
+        std::fs::create_dir_all(dir.path().join("Config")).unwrap();
+        std::fs::write(
+            dir.path().join("Config/App.xcconfig"),
+            "PRODUCT_BUNDLE_IDENTIFIER = com.example.$(PRODUCT_NAME:rfc1034identifier)\n\
+             OTHER_LDFLAGS = -lz\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(dir.path().join("Example")).unwrap();
+        let info_plist = InfoPlist {
+            identification: Identification {
+                bundle_identifier: "$(PRODUCT_BUNDLE_IDENTIFIER)".to_owned(),
+                ..Default::default()
+            },
+            bundle_version: BundleVersion {
+                bundle_short_version_string: Some("$(MARKETING_VERSION)".to_owned()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        plist::to_file_xml(dir.path().join("Example/Info.plist"), &info_plist).unwrap();
+
+        let project = XcodeProject::open(&xcodeproj).unwrap();
+        assert_eq!(project.configuration_names().unwrap(), ["Debug", "Release"]);
+        assert_eq!(
+            project.targets().unwrap(),
+            [XcodeTarget {
+                name: "Example".to_owned(),
+                product_name: Some("Example".to_owned()),
+                product_type: Some("com.apple.product-type.application".to_owned()),
+                configurations: vec!["Debug".to_owned(), "Release".to_owned()],
+            }]
+        );
+
+        let conditions = BuildConditions {
+            sdk: Some("iphonesimulator17.0".to_owned()),
+            ..Default::default()
+        };
+        let debug = project
+            .target_configuration("Example", "Debug", &conditions)
+            .unwrap();
+        assert_eq!(
+            debug.bundle_identifier.as_deref(),
+            Some("com.example.Example.simulator")
+        );
+        assert_eq!(debug.marketing_version.as_deref(), Some("1.0"));
+        assert_eq!(debug.settings.get("CURRENT_PROJECT_VERSION"), Some("3"));
+        assert_eq!(
+            debug.settings.get("OTHER_LDFLAGS"),
+            Some("-lz -ObjC \"-L$(SRCROOT)/My Dir\"")
+        );
+        assert_eq!(
+            debug.info_plist_file,
+            Some(dir.path().join("Example/Info.plist"))
+        );
+        assert_eq!(
+            debug.entitlements_file,
+            Some(dir.path().join("Example/Example.entitlements"))
+        );
+        let expansion = debug.info_plist().unwrap().unwrap();
+        assert!(expansion.unresolved.is_empty());
+        assert_eq!(
+            expansion.value.identification.bundle_identifier,
+            "com.example.Example.simulator"
+        );
+        assert_eq!(
+            expansion
+                .value
+                .bundle_version
+                .bundle_short_version_string
+                .as_deref(),
+            Some("1.0")
+        );
+
+        let release = project
+            .target_configuration("Example", "Release", &BuildConditions::default())
+            .unwrap();
+        assert_eq!(release.marketing_version.as_deref(), Some("2.0"));
+        assert_eq!(release.bundle_identifier, None);
+        assert!(release.info_plist().unwrap().is_none());
+        assert!(project
+            .target_configuration("Missing", "Debug", &conditions)
+            .is_err());
+    }
+
+    #[test]
+    fn rejects_group_cycles() {
+        let pbxproj = PBXPROJ.replace(
+            "children = (F1 /* App.xcconfig */, );",
+            "children = (F1 /* App.xcconfig */, G0, );",
+        );
+        let project = XcodeProject::parse(&pbxproj, "/tmp/Example.xcodeproj").unwrap();
+        assert!(matches!(
+            project.file_path("F1"),
+            Err(Error::InvalidProject(_))
+        ));
+    }
+}
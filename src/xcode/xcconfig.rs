@@ -0,0 +1,349 @@
+use super::build_settings::BuildSettings;
+use crate::{
+    error::{Error, Result},
+    platform::Target,
+};
+use std::path::{Path, PathBuf};
+
+/// A build settings file, `.xcconfig`, with its included files flattened.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct XcConfig {
+    /// The assignments of the file and of the files it includes, in order.
+    pub settings: Vec<XcConfigSetting>,
+}
+
+/// An assignment of an `.xcconfig` file, like `OTHER_LDFLAGS[sdk=iphoneos*] = -lz`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct XcConfigSetting {
+    /// The name of the build setting.
+    pub name: String,
+    /// The conditions that must all match for the assignment to apply.
+    pub conditions: Vec<SettingCondition>,
+    /// The unexpanded value.
+    pub value: String,
+}
+
+/// A condition of an assignment, like `sdk=iphoneos*`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettingCondition {
+    /// The condition key: `sdk`, `arch` or `config`.
+    pub key: String,
+    /// The pattern the value must match, where `*` matches any characters.
+    pub pattern: String,
+}
+
+/// The SDK, architecture and build configuration that conditional assignments are
+/// matched against.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BuildConditions {
+    /// The SDK, like `iphoneos17.0` or `macosx`.
+    pub sdk: Option<String>,
+    /// The architecture, like `arm64`.
+    pub arch: Option<String>,
+    /// The build configuration, like `Release`.
+    pub config: Option<String>,
+}
+
+impl BuildConditions {
+    /// Returns the conditions of a build for a Rust target, with Xcode names for the
+    /// SDK and the architecture.
+    pub fn for_target(target: &Target, config: impl Into<String>) -> Self {
+        let arch = match target.architecture.as_str() {
+            "aarch64" => "arm64",
+            "i686" => "i386",
+            arch => arch,
+        };
+        Self {
+            sdk: Some(target.platform_name().to_owned()),
+            arch: Some(arch.to_owned()),
+            config: Some(config.into()),
+        }
+    }
+
+    fn matches(&self, condition: &SettingCondition) -> bool {
+        let value = match condition.key.as_str() {
+            "sdk" => &self.sdk,
+            "arch" => &self.arch,
+            "config" => &self.config,
+            _ => return false,
+        };
+        value
+            .as_deref()
+            .is_some_and(|value| matches_pattern(&condition.pattern, value))
+    }
+}
+
+impl XcConfig {
+    /// Reads the `.xcconfig` file at `path` and the files it includes.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let mut config = Self::default();
+        config.include(path.as_ref(), &mut Vec::new())?;
+        Ok(config)
+    }
+
+    /// Parses the contents of an `.xcconfig` file, reading included files relative to
+    /// `dir`.
+    pub fn parse(contents: &str, dir: impl AsRef<Path>) -> Result<Self> {
+        let mut config = Self::default();
+        config.parse_lines(contents, "<string>", dir.as_ref(), &mut Vec::new())?;
+        Ok(config)
+    }
+
+    fn include(&mut self, path: &Path, stack: &mut Vec<PathBuf>) -> Result<()> {
+        let canonical = path.canonicalize()?;
+        if stack.contains(&canonical) {
+            return Err(Error::InvalidXcConfig(format!(
+                "{} includes itself",
+                path.display()
+            )));
+        }
+        let contents = std::fs::read_to_string(path)?;
+        stack.push(canonical);
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        self.parse_lines(&contents, &path.display().to_string(), dir, stack)?;
+        stack.pop();
+        Ok(())
+    }
+
+    fn parse_lines(
+        &mut self,
+        contents: &str,
+        file: &str,
+        dir: &Path,
+        stack: &mut Vec<PathBuf>,
+    ) -> Result<()> {
+        for (index, line) in contents.lines().enumerate() {
+            let invalid = |reason: &str| {
+                Error::InvalidXcConfig(format!("{}:{}: {}", file, index + 1, reason))
+            };
+            let line = match line.find("//") {
+                Some(comment) => &line[..comment],
+                None => line,
+            }
+            .trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(directive) = line.strip_prefix("#include") {
+                let (optional, path) = match directive.strip_prefix('?') {
+                    Some(path) => (true, path),
+                    None => (false, directive),
+                };
+                let path = path
+                    .trim()
+                    .strip_prefix('"')
+                    .and_then(|path| path.strip_suffix('"'))
+                    .ok_or_else(|| invalid("expected a quoted path after #include"))?;
+                let path = dir.join(path);
+                if optional && !path.is_file() {
+                    continue;
+                }
+                self.include(&path, stack)?;
+                continue;
+            }
+            let mut depth = 0;
+            let equals = line
+                .find(|c| {
+                    match c {
+                        '[' => depth += 1,
+                        ']' => depth -= 1,
+                        _ => {}
+                    }
+                    c == '=' && depth == 0
+                })
+                .ok_or_else(|| invalid("expected `NAME = value`"))?;
+            let (target, value) = (&line[..equals], &line[equals + 1..]);
+            let setting = parse_setting(target.trim(), value).ok_or_else(|| {
+                invalid(&format!("invalid setting name or condition `{}`", target))
+            })?;
+            self.settings.push(setting);
+        }
+        Ok(())
+    }
+
+    /// Resolves the settings for the given conditions on top of `base`, the settings of
+    /// the lower level, like the project.
+    ///
+    /// Matching assignments apply in order, those with fewer conditions first, so that
+    /// conditional assignments take precedence over unconditional ones.
+    /// `$(inherited)` is replaced with the value the setting had before the
+    /// assignment. Other references are kept, to be expanded by
+    /// [`BuildSettings::expand`].
+    pub fn resolve(&self, conditions: &BuildConditions, base: &BuildSettings) -> BuildSettings {
+        let mut matching: Vec<&XcConfigSetting> = self
+            .settings
+            .iter()
+            .filter(|setting| {
+                setting
+                    .conditions
+                    .iter()
+                    .all(|condition| conditions.matches(condition))
+            })
+            .collect();
+        matching.sort_by_key(|setting| setting.conditions.len());
+        let mut settings = base.clone();
+        for setting in matching {
+            let inherited = settings.get(&setting.name).unwrap_or_default();
+            let value = setting
+                .value
+                .replace("$(inherited)", inherited)
+                .replace("${inherited}", inherited);
+            settings.set(setting.name.clone(), value.trim());
+        }
+        settings
+    }
+}
+
+/// Parses `NAME[key=pattern][key=pattern,key=pattern]` and the value of an assignment.
+pub(crate) fn parse_setting(target: &str, value: &str) -> Option<XcConfigSetting> {
+    let (name, mut rest) = match target.find('[') {
+        Some(index) => (target[..index].trim_end(), &target[index..]),
+        None => (target, ""),
+    };
+    let is_name = |name: &str| {
+        name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    };
+    if !is_name(name) {
+        return None;
+    }
+    let mut conditions = Vec::new();
+    while !rest.is_empty() {
+        let inner = rest.strip_prefix('[')?;
+        let close = inner.find(']')?;
+        for condition in inner[..close].split(',') {
+            let (key, pattern) = condition.split_once('=')?;
+            conditions.push(SettingCondition {
+                key: key.trim().to_owned(),
+                pattern: pattern.trim().to_owned(),
+            });
+        }
+        rest = inner[close + 1..].trim_start();
+    }
+    let value = value.trim();
+    Some(XcConfigSetting {
+        name: name.to_owned(),
+        conditions,
+        value: value
+            .strip_suffix(';')
+            .unwrap_or(value)
+            .trim_end()
+            .to_owned(),
+    })
+}
+
+/// Matches a condition pattern where `*` matches any characters.
+fn matches_pattern(pattern: &str, value: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == value,
+        Some((prefix, rest)) => {
+            let Some(value) = value.strip_prefix(prefix) else {
+                return false;
+            };
+            (0..=value.len())
+                .filter(|&index| value.is_char_boundary(index))
+                .any(|index| matches_pattern(rest, &value[index..]))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn resolves_xcconfig_settings() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("Base.xcconfig"),
+            "// Shared settings\n\
+             PRODUCT_NAME = Example\n\
+             OTHER_LDFLAGS = -lz\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("App.xcconfig"),
+            "#include \"Base.xcconfig\"\n\
+             #include? \"Local.xcconfig\"\n\
+             \n\
+             PRODUCT_BUNDLE_IDENTIFIER = com.example.$(PRODUCT_NAME:rfc1034identifier)\n\
+             OTHER_LDFLAGS[sdk=iphoneos*][arch=arm64] = $(inherited) -framework UIKit\n\
+             OTHER_LDFLAGS = $(inherited) -lc++ // trailing comment\n\
+             MARKETING_VERSION[config=Debug] = 0.0.1\n\
+             MARKETING_VERSION[config=Release,sdk=iphone*] = 1.0;\n",
+        )
+        .unwrap();
+        let config = XcConfig::open(dir.path().join("App.xcconfig")).unwrap();
+        assert_eq!(config.settings.len(), 7);
+        assert_eq!(
+            config.settings[3].conditions,
+            [
+                SettingCondition {
+                    key: "sdk".to_owned(),
+                    pattern: "iphoneos*".to_owned(),
+                },
+                SettingCondition {
+                    key: "arch".to_owned(),
+                    pattern: "arm64".to_owned(),
+                },
+            ]
+        );
+
+        let base = BuildSettings::from_iter([("OTHER_LDFLAGS", "-ObjC")]);
+        let target: Target = "aarch64-apple-ios".parse().unwrap();
+        let settings = config.resolve(&BuildConditions::for_target(&target, "Release"), &base);
+        assert_eq!(
+            settings.get("OTHER_LDFLAGS"),
+            Some("-lz -lc++ -framework UIKit")
+        );
+        assert_eq!(settings.get("MARKETING_VERSION"), Some("1.0"));
+
+        let conditions = BuildConditions {
+            sdk: Some("macosx14.0".to_owned()),
+            config: Some("Debug".to_owned()),
+            ..Default::default()
+        };
+        let settings = config.resolve(&conditions, &base);
+        assert_eq!(settings.get("OTHER_LDFLAGS"), Some("-lz -lc++"));
+        assert_eq!(settings.get("MARKETING_VERSION"), Some("0.0.1"));
+
+        let info_plist = InfoPlist {
+            identification: Identification {
+                bundle_identifier: "$(PRODUCT_BUNDLE_IDENTIFIER)".to_owned(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let expansion = settings.expand(&info_plist).unwrap();
+        assert_eq!(
+            expansion.value.identification.bundle_identifier,
+            "com.example.Example"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_xcconfig() {
+        let dir = tempfile::tempdir().unwrap();
+        for contents in [
+            "NO_VALUE",
+            "BAD NAME = 1",
+            "NAME[sdk=iphoneos = 1",
+            "#include Base.xcconfig",
+            "#include \"Missing.xcconfig\"",
+        ] {
+            assert!(
+                XcConfig::parse(contents, dir.path()).is_err(),
+                "{}",
+                contents
+            );
+        }
+        std::fs::write(
+            dir.path().join("Loop.xcconfig"),
+            "#include \"Loop.xcconfig\"\n",
+        )
+        .unwrap();
+        assert!(XcConfig::open(dir.path().join("Loop.xcconfig")).is_err());
+        assert!(XcConfig::parse("#include? \"Missing.xcconfig\"", dir.path()).is_ok());
+    }
+}